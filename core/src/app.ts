@@ -3,9 +3,18 @@ import cors from "@fastify/cors";
 import multipart from "@fastify/multipart";
 import websocket from "@fastify/websocket";
 import { registerRoutes } from "./routes";
-import { isToolboxDevMode } from "./config/env";
+import { getApiToken, isToolboxDevMode } from "./config/env";
+import { fail } from "./utils/response";
 import { inspect } from "node:util";
-import { buildLoggerConfig } from "./utils/logger";
+import { timingSafeEqual } from "node:crypto";
+import { buildLoggerConfig, redactUrl } from "./utils/logger";
+
+/** 常量时间比较 token，避免按响应耗时逐字节猜测；长度不同直接判否。 */
+function tokenMatches(candidate: unknown, expected: Buffer): boolean {
+  if (typeof candidate !== "string") return false;
+  const actual = Buffer.from(candidate);
+  return actual.length === expected.length && timingSafeEqual(actual, expected);
+}
 
 /**
  * 创建并配置 Fastify 应用
@@ -31,7 +40,7 @@ export async function createApp() {
           app.log.info(
             {
               method: request.method,
-              url: request.url,
+              url: redactUrl(request.url),
               statusCode: reply.statusCode,
               payload: payloadPreview,
             },
@@ -52,7 +61,7 @@ export async function createApp() {
         app.log.error(
           {
             method: request.method,
-            url: request.url,
+            url: redactUrl(request.url),
             statusCode: reply.statusCode,
             message: err instanceof Error ? err.message : String(err),
           },
@@ -96,6 +105,22 @@ export async function createApp() {
     });
   }
 
+  // 设置了 API_TOKEN 时（无窗口模式），除健康检查与预检外都要求 `Authorization: Bearer <token>`；
+  // WebSocket 无法自定义请求头，可改用 `?token=<token>`（日志中的 URL 会脱敏，见 redactUrl）。
+  const apiToken = getApiToken();
+  if (apiToken) {
+    const expected = Buffer.from(apiToken);
+    app.addHook("onRequest", async (request, reply) => {
+      const path = request.url.split("?")[0];
+      if (request.method === "OPTIONS" || path === "/health") return;
+      const header = request.headers.authorization;
+      const bearer = header?.startsWith("Bearer ") ? header.slice(7) : undefined;
+      const query = (request.query as Record<string, unknown> | undefined)?.token;
+      if (tokenMatches(bearer, expected) || tokenMatches(query, expected)) return;
+      return reply.code(401).send(fail("unauthorized", null, 401));
+    });
+  }
+
   // 注册 CORS
   await app.register(cors, {
     origin: true,
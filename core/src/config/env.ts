@@ -34,6 +34,11 @@ export function getApiPort(): number {
   return Number.isInteger(n) && n > 0 && n < 65536 ? n : DEFAULT_API_PORT;
 }
 
+/** 访问令牌：Tauri 以 `--headless` 运行时注入；未设置时不校验（窗口模式仅本机 webview 访问） */
+export function getApiToken(): string | undefined {
+  return readEnvString("API_TOKEN");
+}
+
 export function getDbPath(): string | undefined {
   return readEnvString("DB_PATH");
 }
@@ -42,6 +47,11 @@ export function getStorePath(): string | undefined {
   return readEnvString("STORE_PATH");
 }
 
+/** store 加密时由 Tauri 注入：STORE_PATH 对应文件的派生密钥（base64），用于解密 `$enc` 信封 */
+export function getStoreKey(): string | undefined {
+  return readEnvString("STORE_KEY");
+}
+
 /** 侧车/tsx 统一开发模式开关 */
 export function isToolboxDevMode(): boolean {
   return getToolboxEnv() === "development";
@@ -1,6 +1,6 @@
 import { integer, sqliteTable, text } from "drizzle-orm/sqlite-core";
 
-/** 与前端 src/sql/schema 的 test 表一致 */
+/** 表结构以 src-tauri/migrations/0001_create_test.sql 为准（Tauri 在 core 启动前执行迁移），此处仅供 drizzle 查询使用 */
 export const testTable = sqliteTable("test", {
   id: integer("id").primaryKey({ autoIncrement: true }),
   name: text("name"),
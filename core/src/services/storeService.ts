@@ -1,15 +1,44 @@
 import fse from "fs-extra";
 import type { FastifyBaseLogger } from "fastify";
-import { getStorePath } from "../config/env";
+import { getStoreKey, getStorePath } from "../config/env";
+import { decryptEnvelope, isEnvelope } from "../utils/storeCrypto";
 import { createWatchedFileCache } from "../utils/watchedFileCache";
 
 function getConfigPath(): string | null {
   return getStorePath() ?? null;
 }
 
+/**
+ * store 加密模式下的 value 为 `$enc` 信封：有 STORE_KEY 时逐个解密，解密失败或缺少密钥的 key 省略。
+ * 以 `__` 开头的保留 key（如过期表）不属于业务配置，一并省略。
+ */
+const openEntries = (raw: unknown): unknown => {
+  if (typeof raw !== "object" || raw === null || Array.isArray(raw)) return raw;
+  const b64 = getStoreKey();
+  const key = b64 ? Buffer.from(b64, "base64") : null;
+  const out: Record<string, unknown> = {};
+  for (const [k, v] of Object.entries(raw as Record<string, unknown>)) {
+    if (k.startsWith("__")) continue;
+    if (!isEnvelope(v)) {
+      out[k] = v;
+      continue;
+    }
+    if (!key) {
+      console.warn("[storeService] STORE_KEY not set, skip encrypted key", { key: k });
+      continue;
+    }
+    try {
+      out[k] = decryptEnvelope(key, k, v);
+    } catch (err) {
+      console.warn("[storeService] decrypt failed, skip key", { key: k, err });
+    }
+  }
+  return out;
+};
+
 const readJson = async (p: string): Promise<unknown> => {
   if (!(await fse.pathExists(p))) return null;
-  return fse.readJson(p, { encoding: "utf-8" }) as Promise<unknown>;
+  return openEntries(await fse.readJson(p, { encoding: "utf-8" }));
 };
 
 let cache: ReturnType<typeof createWatchedFileCache> | null = null;
@@ -10,6 +10,7 @@ import {
 import { PassThrough } from "node:stream";
 import pino from "pino";
 import pinoPretty from "pino-pretty";
+import type { FastifyRequest } from "fastify";
 import { isToolboxDevMode } from "../config/env";
 
 const LOG_LEVEL = (process.env.LOG_LEVEL || "info") as string;
@@ -137,6 +138,29 @@ export function getDailyLogFilePathWithCleanup(keepDays = 7): string {
   return path.join(dir, `${baseName}-${ymd}${ext}`);
 }
 
+/**
+ * 把 URL 查询串中 `token` 参数的值替换为 `[REDACTED]`，避免 API token（WebSocket 的 `?token=`）写进日志。
+ */
+export function redactUrl(url: string): string {
+  return url.replace(/([?&]token=)[^&#]*/gi, "$1[REDACTED]");
+}
+
+/**
+ * 覆盖 Fastify 默认的 req 序列化（字段与默认一致），记录请求日志时对 url 脱敏。
+ */
+const serializers = {
+  req(request: FastifyRequest) {
+    return {
+      method: request.method,
+      url: redactUrl(request.url),
+      version: request.headers?.["accept-version"],
+      host: request.host,
+      remoteAddress: request.ip,
+      remotePort: request.socket?.remotePort,
+    };
+  },
+};
+
 /**
  * 构建 Fastify logger 配置。
  * 使用 pino-pretty stream（非 transport），避免 thread-stream 在 bundle 后查找 lib/worker.js 失败。
@@ -175,6 +199,7 @@ export function buildLoggerConfig() {
 
       return {
         level,
+        serializers,
         stream: tee,
         timestamp: pino.stdTimeFunctions.isoTime,
       };
@@ -182,6 +207,7 @@ export function buildLoggerConfig() {
 
     return {
       level,
+      serializers,
       stream: pinoPretty(prettyOptions),
       timestamp: pino.stdTimeFunctions.isoTime,
     };
@@ -193,10 +219,11 @@ export function buildLoggerConfig() {
     // 用 ISO 时间字符串替代默认 epoch 时间；避免你们再做换算。
     return {
       level,
+      serializers,
       file: dailyFilePath,
       timestamp: pino.stdTimeFunctions.isoTime,
     };
   }
 
-  return { level };
+  return { level, serializers };
 }
@@ -0,0 +1,76 @@
+import { createDecipheriv } from "node:crypto";
+
+/**
+ * 解密 Tauri store 加密模式写入的信封（见 src-tauri/src/store/crypto.rs）：
+ * `{"$enc":"xchacha20poly1305","nonce":"<b64 24 字节>","data":"<b64 密文+tag>"}`，AAD 为 key 名。
+ * Node 只内置 IETF ChaCha20-Poly1305，XChaCha20 先用 HChaCha20 派生子密钥再解密。
+ */
+
+const ENVELOPE_TAG = "$enc";
+const ALGORITHM = "xchacha20poly1305";
+const TAG_LEN = 16;
+
+export interface StoreEnvelope {
+  $enc: string;
+  nonce: string;
+  data: string;
+}
+
+export function isEnvelope(value: unknown): value is StoreEnvelope {
+  return (
+    typeof value === "object" &&
+    value !== null &&
+    (value as Record<string, unknown>)[ENVELOPE_TAG] === ALGORITHM
+  );
+}
+
+const rotl = (v: number, c: number): number => ((v << c) | (v >>> (32 - c))) >>> 0;
+
+/** HChaCha20：32 字节密钥 + 16 字节 nonce → 32 字节子密钥 */
+function hchacha20(key: Buffer, nonce: Buffer): Buffer {
+  const s = new Uint32Array(16);
+  s.set([0x61707865, 0x3320646e, 0x79622d32, 0x6b206574]);
+  for (let i = 0; i < 8; i++) s[4 + i] = key.readUInt32LE(i * 4);
+  for (let i = 0; i < 4; i++) s[12 + i] = nonce.readUInt32LE(i * 4);
+  const qr = (a: number, b: number, c: number, d: number): void => {
+    s[a] += s[b];
+    s[d] = rotl(s[d] ^ s[a], 16);
+    s[c] += s[d];
+    s[b] = rotl(s[b] ^ s[c], 12);
+    s[a] += s[b];
+    s[d] = rotl(s[d] ^ s[a], 8);
+    s[c] += s[d];
+    s[b] = rotl(s[b] ^ s[c], 7);
+  };
+  for (let i = 0; i < 10; i++) {
+    // 列轮
+    qr(0, 4, 8, 12);
+    qr(1, 5, 9, 13);
+    qr(2, 6, 10, 14);
+    qr(3, 7, 11, 15);
+    // 对角轮
+    qr(0, 5, 10, 15);
+    qr(1, 6, 11, 12);
+    qr(2, 7, 8, 13);
+    qr(3, 4, 9, 14);
+  }
+  const out = Buffer.alloc(32);
+  [0, 1, 2, 3, 12, 13, 14, 15].forEach((idx, i) => out.writeUInt32LE(s[idx], i * 4));
+  return out;
+}
+
+/** 解密一个信封；`storeKey` 为 store 中的 key 名（AAD），密钥或数据不匹配时抛错 */
+export function decryptEnvelope(key: Buffer, storeKey: string, env: StoreEnvelope): unknown {
+  const nonce = Buffer.from(env.nonce, "base64");
+  const data = Buffer.from(env.data, "base64");
+  if (key.length !== 32 || nonce.length !== 24 || data.length < TAG_LEN) {
+    throw new Error(`invalid store envelope: ${storeKey}`);
+  }
+  const subkey = hchacha20(key, nonce.subarray(0, 16));
+  const iv = Buffer.concat([Buffer.alloc(4), nonce.subarray(16)]);
+  const decipher = createDecipheriv("chacha20-poly1305", subkey, iv, { authTagLength: TAG_LEN });
+  decipher.setAAD(Buffer.from(storeKey, "utf-8"), { plaintextLength: data.length - TAG_LEN });
+  decipher.setAuthTag(data.subarray(data.length - TAG_LEN));
+  const plain = Buffer.concat([decipher.update(data.subarray(0, data.length - TAG_LEN)), decipher.final()]);
+  return JSON.parse(plain.toString("utf-8"));
+}
@@ -6,6 +6,7 @@
  */
 import { defineConfig } from "tsup";
 import { execSync } from "child_process";
+import crypto from "crypto";
 import fs from "fs";
 import path from "path";
 import { fileURLToPath } from "url";
@@ -230,6 +231,35 @@ function pruneCoreNodeModules() {
   console.log("[core] 裁剪后 node_modules 约 " + sizeMB + " MB");
 }
 
+/** 与 src-tauri/src/integrity.rs 的 MANIFEST_FILE_NAME 一致 */
+const INTEGRITY_FILE = "integrity.json";
+
+/** 递归列出普通文件（相对 root、以 / 分隔），跳过符号链接 */
+function listFiles(root: string, dir: string, out: string[]) {
+  for (const e of fs.readdirSync(dir, { withFileTypes: true })) {
+    const full = path.join(dir, e.name);
+    if (e.isDirectory()) listFiles(root, full, out);
+    else if (e.isFile()) out.push(path.relative(root, full).split(path.sep).join("/"));
+  }
+}
+
+/**
+ * 生成 resources/core/integrity.json：每个文件的 SHA-256。
+ * Tauri 每次启动 core 前按此校验（见 src-tauri/src/integrity.rs），因此必须在 node_modules 安装 / 裁剪之后执行。
+ */
+function writeIntegrityManifest() {
+  const files: string[] = [];
+  listFiles(RESOURCES_CORE, RESOURCES_CORE, files);
+  const hashes: Record<string, string> = {};
+  for (const rel of files.filter((f) => f !== INTEGRITY_FILE).sort()) {
+    const data = fs.readFileSync(path.join(RESOURCES_CORE, rel));
+    hashes[rel] = crypto.createHash("sha256").update(data).digest("hex");
+  }
+  const manifest = { algorithm: "sha256", generatedAt: new Date().toISOString(), files: hashes };
+  fs.writeFileSync(path.join(RESOURCES_CORE, INTEGRITY_FILE), JSON.stringify(manifest, null, 2), "utf8");
+  console.log("[core] 已写入 " + INTEGRITY_FILE + "（" + Object.keys(hashes).length + " 个文件）");
+}
+
 export default defineConfig({
   entry: ["index.ts"],
   outDir: "../src-tauri/resources/core",
@@ -273,5 +303,6 @@ export default defineConfig({
       }
     }
     installCoreNodeModules();
+    writeIntegrityManifest();
   },
 });
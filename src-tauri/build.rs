@@ -1,3 +1,17 @@
+use sha2::{Digest, Sha256};
+
+/// 内置 core 清单（`resources/core/integrity.json`）的 SHA-256 嵌入二进制，
+/// 运行时以此校验清单本身，见 `src/integrity.rs`。清单不存在时嵌入空串。
+fn embed_core_integrity_digest() {
+    let manifest = "resources/core/integrity.json";
+    println!("cargo:rerun-if-changed={}", manifest);
+    let digest = std::fs::read(manifest)
+        .map(|bytes| format!("{:x}", Sha256::digest(bytes)))
+        .unwrap_or_default();
+    println!("cargo:rustc-env=CORE_INTEGRITY_SHA256={}", digest);
+}
+
 fn main() {
+    embed_core_integrity_digest();
     tauri_build::build()
 }
@@ -0,0 +1,5 @@
+-- test 表：与 core/src/db/schema.ts、src/sql/schema.ts 中的 drizzle 定义一致
+CREATE TABLE IF NOT EXISTS test (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  name TEXT
+);
@@ -0,0 +1,478 @@
+//! `clawbot_download`：按发布清单下载 Clawbot 压缩包到 `clawbot/releases/<version>.<ext>`，
+//! 校验通过后解压到 `clawbot/versions/<version>/`（见 [super::extract]）并切换为当前版本（见 [super::versions]）。
+//!
+//! - 清单地址取自 settings.json 的 `clawbot_manifest_url`（支持 `file://`，便于本地测试）。
+//! - 先写 `<version>.<ext>.part`，再次下载时用 HTTP Range 续传；服务端不支持 Range 时从头下载。
+//! - 下载完成后校验清单中的 SHA-256，不一致则删除并报错，校验通过才 rename 为正式文件。
+//! - 过程中向前端发送 `clawbot-download-progress` 事件（bytes / total / rate）。
+//! - 压缩包复用且 `versions/<version>/` 已存在时不重新解压；需要替换的正是运行中 Clawbot 的目录时，
+//!   先停止服务，解压完成后再重新启动。下载的已是当前版本时不再切换。
+//!
+//! 清单格式：
+//!
+//! ```json
+//! {
+//!   "latest": "1.2.0",
+//!   "releases": {
+//!     "1.2.0": {
+//!       "linux-x86_64": { "url": "https://…/clawbot-1.2.0-linux-x86_64.tar.gz", "sha256": "…" }
+//!     }
+//!   }
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_http::reqwest;
+
+use super::extract::{self, MAX_UNCOMPRESSED_BYTES};
+use super::{check_version_name, clawbot_dir, read_state, service, update_state, versions};
+use crate::config;
+use crate::util::sha256_file;
+
+const RELEASES_DIR_NAME: &str = "releases";
+const PART_SUFFIX: &str = ".part";
+/// 进度事件最小间隔，避免刷屏。
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 进程内同时只允许一个下载任务；检查与占用用 compare_exchange 一步完成。
+static DOWNLOADING: AtomicBool = AtomicBool::new(false);
+
+/// 持有期间占用 [DOWNLOADING]，drop 时释放（含提前返回的路径）。
+struct DownloadGuard;
+
+impl DownloadGuard {
+    fn acquire() -> Option<Self> {
+        DOWNLOADING
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| DownloadGuard)
+    }
+}
+
+impl Drop for DownloadGuard {
+    fn drop(&mut self) {
+        DOWNLOADING.store(false, Ordering::Release);
+    }
+}
+
+/// 清单中某平台的发布包。
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseAsset {
+    pub url: String,
+    pub sha256: String,
+}
+
+/// 发布清单：`releases` 为 版本 → 平台 → 发布包。
+#[derive(Debug, Deserialize)]
+pub struct ReleaseManifest {
+    pub latest: String,
+    pub releases: HashMap<String, HashMap<String, ReleaseAsset>>,
+}
+
+/// `clawbot-download-progress` 事件负载。
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadProgress {
+    pub version: String,
+    pub bytes: u64,
+    pub total: Option<u64>,
+    /// 字节 / 秒（本次请求内的平均速率）
+    pub rate: u64,
+}
+
+/// 下载并安装完成后的结果，供前端提示。
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadResult {
+    pub version: String,
+    pub archive: PathBuf,
+    pub size: u64,
+    pub install_dir: PathBuf,
+}
+
+/// 当前平台在清单中的 key，如 `linux-x86_64`、`macos-aarch64`、`windows-x86_64`。
+pub fn platform_key() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// 根据 URL 推断压缩包扩展名，仅支持 zip 与 tar.gz。
+pub fn archive_extension(url: &str) -> Result<&'static str, String> {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    if path.ends_with(".zip") {
+        Ok("zip")
+    } else if path.ends_with(".tar.gz") || path.ends_with(".tgz") {
+        Ok("tar.gz")
+    } else {
+        Err(format!("不支持的压缩包格式: {}", url))
+    }
+}
+
+/// `<clawbot>/releases`
+pub fn releases_dir(clawbot: &Path) -> PathBuf {
+    clawbot.join(RELEASES_DIR_NAME)
+}
+
+/// 读取文本：`file://` 直接读本地文件，其余走 HTTP GET。
+pub(crate) async fn fetch_text(url: &str) -> Result<String, String> {
+    if let Some(path) = url.strip_prefix("file://") {
+        return fs::read_to_string(path).map_err(|e| format!("读取 {} 失败: {}", path, e));
+    }
+    let resp = reqwest::get(url)
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("请求 {} 失败: {}", url, e))?;
+    resp.text().await.map_err(|e| e.to_string())
+}
+
+/// 读取发布清单。
+pub async fn fetch_manifest(app: &AppHandle) -> Result<ReleaseManifest, String> {
+    let url = config::get_clawbot_manifest_url(app)
+        .ok_or_else(|| "未配置 clawbot_manifest_url".to_string())?;
+    let text = fetch_text(&url).await?;
+    serde_json::from_str(&text).map_err(|e| format!("发布清单格式错误: {}", e))
+}
+
+/// 把 `url` 下载到 `dest`：已有 `.part` 时续传，完成并校验 SHA-256 后 rename 为 `dest`。
+/// `on_progress` 按 [PROGRESS_INTERVAL] 节流回调 (已下载字节, 总字节, 速率)。
+pub async fn download_verified(
+    url: &str,
+    expected_sha256: &str,
+    dest: &Path,
+    mut on_progress: impl FnMut(u64, Option<u64>, u64),
+) -> Result<u64, String> {
+    let part = PathBuf::from(format!("{}{}", dest.display(), PART_SUFFIX));
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let mut offset = fs::metadata(&part).map(|m| m.len()).unwrap_or(0);
+    let client = reqwest::Client::new();
+    let mut req = client.get(url);
+    if offset > 0 {
+        req = req.header(reqwest::header::RANGE, format!("bytes={}-", offset));
+    }
+    let mut resp = req
+        .send()
+        .await
+        .map_err(|e| format!("请求 {} 失败: {}", url, e))?;
+
+    let status = resp.status();
+    if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        // .part 已不小于远端文件，丢弃后由调用方重试
+        let _ = fs::remove_file(&part);
+        return Err("续传位置无效，已清理未完成文件，请重试".to_string());
+    }
+    if !status.is_success() {
+        return Err(format!("下载失败: HTTP {}", status));
+    }
+    // 请求了 Range 却拿到 200：服务端不支持续传，从头写
+    let resumed = offset > 0 && status == reqwest::StatusCode::PARTIAL_CONTENT;
+    if !resumed {
+        offset = 0;
+    }
+    let total = resp.content_length().map(|len| len + offset);
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(&part)
+        .map_err(|e| format!("打开 {} 失败: {}", part.display(), e))?;
+
+    let started = Instant::now();
+    let mut last_emit = Instant::now();
+    let mut received: u64 = 0;
+    while let Some(chunk) = resp.chunk().await.map_err(|e| format!("下载中断: {}", e))? {
+        file.write_all(&chunk).map_err(|e| e.to_string())?;
+        received += chunk.len() as u64;
+        if last_emit.elapsed() >= PROGRESS_INTERVAL {
+            last_emit = Instant::now();
+            let secs = started.elapsed().as_secs_f64().max(0.001);
+            on_progress(offset + received, total, (received as f64 / secs) as u64);
+        }
+    }
+    file.flush().map_err(|e| e.to_string())?;
+    drop(file);
+
+    let size = offset + received;
+    let secs = started.elapsed().as_secs_f64().max(0.001);
+    on_progress(size, total, (received as f64 / secs) as u64);
+
+    let actual = sha256_file(&part)?;
+    if !actual.eq_ignore_ascii_case(expected_sha256.trim()) {
+        let _ = fs::remove_file(&part);
+        return Err(format!(
+            "SHA-256 校验失败: 期望 {}，实际 {}",
+            expected_sha256, actual
+        ));
+    }
+    fs::rename(&part, dest).map_err(|e| e.to_string())?;
+    Ok(size)
+}
+
+/// 在阻塞线程中把压缩包解压到 `versions/<version>/`，返回安装目录。
+async fn install_archive(clawbot: &Path, archive: &Path, version: &str) -> Result<PathBuf, String> {
+    let dest = extract::versions_dir(clawbot).join(version);
+    let archive = archive.to_path_buf();
+    tauri::async_runtime::spawn_blocking(move || {
+        extract::extract_archive(&archive, &dest, MAX_UNCOMPRESSED_BYTES).map(|_| dest)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// 下载并安装指定版本（缺省为清单中的 latest）的 Clawbot 发布包。
+/// 已有同版本且校验通过的压缩包时直接复用，不重复下载。
+#[tauri::command]
+pub async fn clawbot_download(
+    app: AppHandle,
+    version: Option<String>,
+) -> Result<DownloadResult, String> {
+    let dir = clawbot_dir(&app)?;
+    let manifest = fetch_manifest(&app).await?;
+    let version = version.unwrap_or_else(|| manifest.latest.clone());
+    check_version_name(&version)?;
+    let asset = manifest
+        .releases
+        .get(&version)
+        .and_then(|r| r.get(&platform_key()))
+        .cloned()
+        .ok_or_else(|| format!("清单中没有 {} 的 {} 发布包", version, platform_key()))?;
+    let ext = archive_extension(&asset.url)?;
+    let archive = releases_dir(&dir).join(format!("{}.{}", version, ext));
+
+    let Some(_guard) = DownloadGuard::acquire() else {
+        let running = read_state(&dir).download_in_progress;
+        return Err(format!(
+            "已有下载任务进行中: {}",
+            running.unwrap_or_default()
+        ));
+    };
+    update_state(&dir, |s| {
+        s.download_in_progress = Some(version.clone());
+        s.last_error = None;
+    })?;
+
+    let reusable = archive.exists()
+        && sha256_file(&archive).is_ok_and(|h| h.eq_ignore_ascii_case(asset.sha256.trim()));
+    let downloaded = if reusable {
+        fs::metadata(&archive).map(|m| m.len()).map_err(|e| e.to_string())
+    } else {
+        let _ = fs::remove_file(&archive);
+        let emit_app = app.clone();
+        let emit_version = version.clone();
+        download_verified(&asset.url, &asset.sha256, &archive, move |bytes, total, rate| {
+            let _ = emit_app.emit(
+                "clawbot-download-progress",
+                DownloadProgress {
+                    version: emit_version.clone(),
+                    bytes,
+                    total,
+                    rate,
+                },
+            );
+        })
+        .await
+    };
+
+    let version_dir = extract::versions_dir(&dir).join(&version);
+    // 解压会整体替换目录，已安装的同一压缩包无需再解压
+    let already_installed = reusable && version_dir.is_dir();
+    let is_current = read_state(&dir).current_version.as_deref() == Some(version.as_str());
+    let mut stopped = false;
+    let installed = match &downloaded {
+        Ok(_) if already_installed => Ok(version_dir),
+        Ok(_) => {
+            // 不能在运行中的 Clawbot 脚下替换它的版本目录
+            let stop = if is_current {
+                service::stop(&app)
+            } else {
+                Ok(false)
+            };
+            match stop {
+                Ok(was_running) => {
+                    stopped = was_running;
+                    install_archive(&dir, &archive, &version).await
+                }
+                Err(e) => Err(e),
+            }
+        }
+        Err(e) => Err(e.clone()),
+    };
+    if stopped {
+        if let Err(e) = service::start(&app) {
+            log::warn!("替换目录后重新启动 Clawbot 失败: {}", e);
+        }
+    }
+
+    update_state(&dir, |s| {
+        s.download_in_progress = None;
+        if let Ok(size) = &downloaded {
+            s.push_history("download", Some(&version), Some(format!("{} bytes", size)));
+        }
+        match &installed {
+            Ok(_) if already_installed => {}
+            Ok(_) => s.push_history("install", Some(&version), None),
+            Err(e) => {
+                s.last_error = Some(e.clone());
+                s.push_history("error", Some(&version), Some(e.clone()));
+            }
+        }
+    })?;
+
+    let install_dir = installed?;
+    // 已是当前版本时无需切换（切换会重启运行中的 Clawbot）
+    if !is_current {
+        if let Err(e) = versions::activate(&app, &version) {
+            update_state(&dir, |s| {
+                s.last_error = Some(e.clone());
+                s.push_history("error", Some(&version), Some(e.clone()));
+            })?;
+            return Err(e);
+        }
+    }
+    Ok(DownloadResult {
+        version,
+        archive,
+        size: downloaded?,
+        install_dir,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::sha256_hex;
+    use std::io::BufRead;
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    const BODY: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+    /// 单连接的 HTTP 桩：`honor_range` 为 false 时忽略 Range、总是返回 200 全量。
+    /// 返回 (url, 收到的 Range 头)。
+    fn serve(honor_range: bool) -> (String, Arc<Mutex<Option<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/asset.tar.gz", listener.local_addr().unwrap());
+        let seen = Arc::new(Mutex::new(None));
+        let seen_range = seen.clone();
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+            let mut range = None;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("range") {
+                        range = Some(value.trim().to_string());
+                    }
+                }
+            }
+            *seen_range.lock().unwrap() = range.clone();
+            let start = range
+                .filter(|_| honor_range)
+                .and_then(|r| r.strip_prefix("bytes=")?.strip_suffix('-')?.parse().ok());
+            let mut stream = stream;
+            let head = match start {
+                Some(start) => format!(
+                    "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nContent-Range: bytes {}-{}/{}\r\nConnection: close\r\n\r\n",
+                    BODY.len() - start,
+                    start,
+                    BODY.len() - 1,
+                    BODY.len()
+                ),
+                None => format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    BODY.len()
+                ),
+            };
+            stream.write_all(head.as_bytes()).unwrap();
+            stream.write_all(&BODY[start.unwrap_or(0)..]).unwrap();
+        });
+        (url, seen)
+    }
+
+    fn temp_dest(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("clawbot-download-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("asset.tar.gz")
+    }
+
+    fn part_of(dest: &Path) -> PathBuf {
+        PathBuf::from(format!("{}{}", dest.display(), PART_SUFFIX))
+    }
+
+    fn body_sha256() -> String {
+        sha256_hex(BODY)
+    }
+
+    #[test]
+    fn resumes_from_partial_file_with_range() {
+        let (url, seen) = serve(true);
+        let dest = temp_dest("resume");
+        fs::write(part_of(&dest), &BODY[..10]).unwrap();
+
+        let size = tauri::async_runtime::block_on(download_verified(
+            &url,
+            &body_sha256(),
+            &dest,
+            |_, _, _| {},
+        ))
+        .unwrap();
+
+        assert_eq!(seen.lock().unwrap().as_deref(), Some("bytes=10-"));
+        assert_eq!(size, BODY.len() as u64);
+        assert_eq!(fs::read(&dest).unwrap(), BODY);
+        assert!(!part_of(&dest).exists());
+    }
+
+    #[test]
+    fn restarts_when_server_ignores_range() {
+        let (url, seen) = serve(false);
+        let dest = temp_dest("no-range");
+        fs::write(part_of(&dest), b"stale-bytes").unwrap();
+
+        let size = tauri::async_runtime::block_on(download_verified(
+            &url,
+            &body_sha256(),
+            &dest,
+            |_, _, _| {},
+        ))
+        .unwrap();
+
+        assert_eq!(seen.lock().unwrap().as_deref(), Some("bytes=11-"));
+        assert_eq!(size, BODY.len() as u64);
+        assert_eq!(fs::read(&dest).unwrap(), BODY);
+    }
+
+    #[test]
+    fn rejects_sha256_mismatch_and_removes_partial_file() {
+        let (url, _) = serve(true);
+        let dest = temp_dest("mismatch");
+
+        let result = tauri::async_runtime::block_on(download_verified(
+            &url,
+            &"0".repeat(64),
+            &dest,
+            |_, _, _| {},
+        ));
+
+        assert!(result.unwrap_err().contains("SHA-256"));
+        assert!(!dest.exists());
+        assert!(!part_of(&dest).exists());
+    }
+}
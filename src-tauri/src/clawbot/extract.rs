@@ -0,0 +1,422 @@
+//! Clawbot 发布包解压：zip 与 tar.gz，解压到 `clawbot/versions/<version>/`。
+//!
+//! 安全约束：
+//! - 拒绝 zip-slip（含 `..`）与绝对路径条目；
+//! - 符号链接在普通文件全部写完后才创建（避免经由链接写到外部），且解析后须位于解压目录内部，否则整体失败；
+//! - 按实际写出的字节数累计，超过上限（默认 [MAX_UNCOMPRESSED_BYTES]）即中止，防压缩炸弹；
+//! - 先解压到同级临时目录，全部成功后再 rename 到目标目录，失败时清理临时目录。
+//!
+//! Unix 下保留条目中的可执行位。
+
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+
+use flate2::read::GzDecoder;
+
+/// 解压后总大小上限：2 GiB。
+pub const MAX_UNCOMPRESSED_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// `<clawbot>/versions`
+pub fn versions_dir(clawbot: &Path) -> PathBuf {
+    clawbot.join("versions")
+}
+
+/// 按扩展名解压 `archive` 到 `dest`（先临时目录再 rename，`dest` 已存在时被替换）。
+pub fn extract_archive(archive: &Path, dest: &Path, max_bytes: u64) -> Result<(), String> {
+    let name = archive.to_string_lossy().to_string();
+    let parent = dest
+        .parent()
+        .ok_or_else(|| format!("无效的解压目录: {}", dest.display()))?;
+    fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    let dest_name = dest
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let tmp = parent.join(format!(".{}.extracting", dest_name));
+    if tmp.exists() {
+        fs::remove_dir_all(&tmp).map_err(|e| e.to_string())?;
+    }
+    fs::create_dir_all(&tmp).map_err(|e| e.to_string())?;
+
+    let result = if name.ends_with(".zip") {
+        extract_zip(archive, &tmp, max_bytes)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        extract_tar_gz(archive, &tmp, max_bytes)
+    } else {
+        Err(format!("不支持的压缩包格式: {}", name))
+    };
+    if let Err(e) = result {
+        let _ = fs::remove_dir_all(&tmp);
+        return Err(e);
+    }
+
+    if dest.exists() {
+        fs::remove_dir_all(dest).map_err(|e| format!("清理旧目录失败: {}", e))?;
+    }
+    fs::rename(&tmp, dest).map_err(|e| format!("移动解压目录失败: {}", e))
+}
+
+/// 条目路径须为不含 `..`、非绝对的相对路径，返回规范化后的相对路径。
+fn sanitize_entry_path(raw: &Path) -> Result<PathBuf, String> {
+    let mut out = PathBuf::new();
+    for comp in raw.components() {
+        match comp {
+            Component::Normal(p) => out.push(p),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(format!("压缩包含非法路径: {}", raw.display()));
+            }
+        }
+    }
+    if out.as_os_str().is_empty() {
+        return Err(format!("压缩包含空路径条目: {}", raw.display()));
+    }
+    Ok(out)
+}
+
+/// 校验符号链接 `link`（相对解压根）指向 `target` 后仍位于解压根内。
+fn check_symlink_target(link: &Path, target: &Path) -> Result<(), String> {
+    if target.is_absolute() {
+        return Err(format!("符号链接指向绝对路径: {} -> {}", link.display(), target.display()));
+    }
+    let mut depth: Vec<&std::ffi::OsStr> = Vec::new();
+    let base = link.parent().unwrap_or(Path::new(""));
+    for comp in base.components().chain(target.components()) {
+        match comp {
+            Component::Normal(p) => depth.push(p),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if depth.pop().is_none() {
+                    return Err(format!(
+                        "符号链接越出解压目录: {} -> {}",
+                        link.display(),
+                        target.display()
+                    ));
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => {
+                return Err(format!("符号链接目标非法: {}", target.display()));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn make_symlink(target: &Path, link: &Path) -> Result<(), String> {
+    std::os::unix::fs::symlink(target, link).map_err(|e| e.to_string())
+}
+
+#[cfg(not(unix))]
+fn make_symlink(target: &Path, link: &Path) -> Result<(), String> {
+    Err(format!(
+        "当前平台不支持解压符号链接: {} -> {}",
+        link.display(),
+        target.display()
+    ))
+}
+
+/// 创建全部符号链接，再逐个按实际解析结果复核：悬空或解析到解压根之外都视为失败。
+/// 单看字面路径不够（如 `p -> .` 之后 `q -> p/..`），因此在全部链接就位后用 canonicalize 复核。
+fn create_symlinks(root: &Path, links: &[(PathBuf, PathBuf)]) -> Result<(), String> {
+    for (rel, target) in links {
+        let out = root.join(rel);
+        if let Some(parent) = out.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        make_symlink(target, &out)?;
+    }
+    let canonical_root = fs::canonicalize(root).map_err(|e| e.to_string())?;
+    for (rel, target) in links {
+        let resolved = fs::canonicalize(root.join(rel)).map_err(|_| {
+            format!("符号链接无法解析: {} -> {}", rel.display(), target.display())
+        })?;
+        if !resolved.starts_with(&canonical_root) {
+            return Err(format!(
+                "符号链接越出解压目录: {} -> {}",
+                rel.display(),
+                target.display()
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) {
+    use std::os::unix::fs::PermissionsExt;
+    // 只保留权限位，去掉 setuid / setgid / sticky
+    let _ = fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o777));
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) {}
+
+/// 把 `reader` 写入 `out`，累计到 `written` 并在超过 `max_bytes` 时报错。
+fn copy_limited(
+    reader: &mut impl Read,
+    out: &Path,
+    written: &mut u64,
+    max_bytes: u64,
+) -> Result<(), String> {
+    if let Some(parent) = out.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut file = fs::File::create(out).map_err(|e| e.to_string())?;
+    let remaining = max_bytes.saturating_sub(*written);
+    let n = io::copy(&mut reader.take(remaining + 1), &mut file).map_err(|e| e.to_string())?;
+    *written += n;
+    if *written > max_bytes {
+        return Err(format!("解压后大小超过上限 {} 字节，疑似压缩炸弹", max_bytes));
+    }
+    Ok(())
+}
+
+fn extract_zip(archive: &Path, root: &Path, max_bytes: u64) -> Result<(), String> {
+    let file = fs::File::open(archive).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipArchive::new(file).map_err(|e| format!("读取 zip 失败: {}", e))?;
+    let mut written = 0u64;
+    let mut links = Vec::new();
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i).map_err(|e| e.to_string())?;
+        let rel = sanitize_entry_path(Path::new(entry.name()))?;
+        let out = root.join(&rel);
+        if entry.is_dir() {
+            fs::create_dir_all(&out).map_err(|e| e.to_string())?;
+            continue;
+        }
+        if entry.is_symlink() {
+            let mut target = String::new();
+            entry.read_to_string(&mut target).map_err(|e| e.to_string())?;
+            check_symlink_target(&rel, Path::new(&target))?;
+            links.push((rel, PathBuf::from(target)));
+            continue;
+        }
+        copy_limited(&mut entry, &out, &mut written, max_bytes)?;
+        if let Some(mode) = entry.unix_mode() {
+            set_mode(&out, mode);
+        }
+    }
+    create_symlinks(root, &links)
+}
+
+fn extract_tar_gz(archive: &Path, root: &Path, max_bytes: u64) -> Result<(), String> {
+    let file = fs::File::open(archive).map_err(|e| e.to_string())?;
+    let mut tar = tar::Archive::new(GzDecoder::new(file));
+    let mut written = 0u64;
+    let mut links = Vec::new();
+    for entry in tar.entries().map_err(|e| format!("读取 tar.gz 失败: {}", e))? {
+        let mut entry = entry.map_err(|e| e.to_string())?;
+        let raw = entry.path().map_err(|e| e.to_string())?.into_owned();
+        let kind = entry.header().entry_type();
+        // pax 扩展头等元数据条目不落盘
+        if kind.is_pax_global_extensions() || kind.is_pax_local_extensions() || kind.is_gnu_longname() {
+            continue;
+        }
+        let rel = match sanitize_entry_path(&raw) {
+            Ok(p) => p,
+            // tar 常见的根条目 "./"
+            Err(_) if raw.components().all(|c| c == Component::CurDir) => continue,
+            Err(e) => return Err(e),
+        };
+        let out = root.join(&rel);
+        if kind.is_dir() {
+            fs::create_dir_all(&out).map_err(|e| e.to_string())?;
+        } else if kind.is_symlink() {
+            let target = entry
+                .link_name()
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("符号链接缺少目标: {}", raw.display()))?
+                .into_owned();
+            check_symlink_target(&rel, &target)?;
+            links.push((rel, target));
+        } else if kind.is_file() {
+            let mode = entry.header().mode().ok();
+            copy_limited(&mut entry, &out, &mut written, max_bytes)?;
+            if let Some(mode) = mode {
+                set_mode(&out, mode);
+            }
+        } else {
+            // 硬链接、设备文件等一律拒绝
+            return Err(format!("压缩包含不支持的条目类型: {}", raw.display()));
+        }
+    }
+    create_symlinks(root, &links)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    /// 每个测试独立的临时目录（进程号 + 用例名），开始前清空。
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "clawbot-extract-test-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// 构造 tar.gz；`add` 可直接写原始头部，绕过 tar::Builder 对路径的校验。
+    fn write_tar_gz(path: &Path, add: impl FnOnce(&mut tar::Builder<GzEncoder<fs::File>>)) {
+        let gz = GzEncoder::new(fs::File::create(path).unwrap(), Compression::default());
+        let mut builder = tar::Builder::new(gz);
+        add(&mut builder);
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    /// 按原始名称追加一个条目（名称原样写入头部，可含 `..` 或以 `/` 开头）。
+    fn append_raw(
+        builder: &mut tar::Builder<GzEncoder<fs::File>>,
+        name: &str,
+        kind: tar::EntryType,
+        link: Option<&str>,
+        data: &[u8],
+    ) {
+        let mut header = tar::Header::new_gnu();
+        header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+        if let Some(link) = link {
+            header.as_old_mut().linkname[..link.len()].copy_from_slice(link.as_bytes());
+        }
+        header.set_entry_type(kind);
+        header.set_mode(0o755);
+        header.set_size(data.len() as u64);
+        header.set_cksum();
+        builder.append(&header, data).unwrap();
+    }
+
+    fn write_zip(path: &Path, files: &[(&str, &[u8])]) {
+        let mut zip = zip::ZipWriter::new(fs::File::create(path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        for (name, data) in files {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn sanitize_rejects_parent_and_absolute() {
+        assert_eq!(
+            sanitize_entry_path(Path::new("./bin/clawbot")).unwrap(),
+            PathBuf::from("bin/clawbot")
+        );
+        assert!(sanitize_entry_path(Path::new("../evil")).is_err());
+        assert!(sanitize_entry_path(Path::new("bin/../../evil")).is_err());
+        assert!(sanitize_entry_path(Path::new("/etc/passwd")).is_err());
+        assert!(sanitize_entry_path(Path::new("./")).is_err());
+    }
+
+    #[test]
+    fn symlink_target_stays_inside_root() {
+        assert!(check_symlink_target(Path::new("bin/run"), Path::new("../lib/run")).is_ok());
+        assert!(check_symlink_target(Path::new("bin/run"), Path::new("../../run")).is_err());
+        assert!(check_symlink_target(Path::new("run"), Path::new("/usr/bin/env")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn create_symlinks_checks_resolved_path() {
+        let root = temp_dir("symlinks");
+        fs::write(root.join("a"), b"a").unwrap();
+        let ok = [(PathBuf::from("bin/a"), PathBuf::from("../a"))];
+        assert!(create_symlinks(&root, &ok).is_ok());
+
+        // 逐个看字面路径都在根内，但 q 实际解析为 root/..
+        let root = temp_dir("symlinks-chain");
+        let links = [
+            (PathBuf::from("p"), PathBuf::from(".")),
+            (PathBuf::from("q"), PathBuf::from("p/..")),
+        ];
+        for (link, target) in &links {
+            assert!(check_symlink_target(link, target).is_ok());
+        }
+        let err = create_symlinks(&root, &links).unwrap_err();
+        assert!(err.contains("越出解压目录"), "{}", err);
+
+        let dangling = temp_dir("symlinks-dangling");
+        let links = [(PathBuf::from("x"), PathBuf::from("missing"))];
+        assert!(create_symlinks(&dangling, &links).is_err());
+    }
+
+    #[test]
+    fn copy_limited_enforces_cap() {
+        let dir = temp_dir("copy-limited");
+        let mut written = 0u64;
+        copy_limited(&mut &b"12345"[..], &dir.join("a"), &mut written, 8).unwrap();
+        assert_eq!(written, 5);
+        // 累计 10 字节超过 8 字节上限
+        let err = copy_limited(&mut &b"67890"[..], &dir.join("b"), &mut written, 8).unwrap_err();
+        assert!(err.contains("上限"), "{}", err);
+    }
+
+    #[test]
+    fn extracts_zip_and_tar_gz() {
+        let dir = temp_dir("extract-ok");
+        let zip_path = dir.join("a.zip");
+        write_zip(&zip_path, &[("bin/clawbot", b"zip"), ("README", b"hi")]);
+        extract_archive(&zip_path, &dir.join("z"), MAX_UNCOMPRESSED_BYTES).unwrap();
+        assert_eq!(fs::read(dir.join("z/bin/clawbot")).unwrap(), b"zip");
+
+        let tar_path = dir.join("a.tar.gz");
+        write_tar_gz(&tar_path, |b| {
+            append_raw(b, "./", tar::EntryType::Directory, None, b"");
+            append_raw(b, "bin/clawbot", tar::EntryType::Regular, None, b"tar");
+        });
+        extract_archive(&tar_path, &dir.join("t"), MAX_UNCOMPRESSED_BYTES).unwrap();
+        assert_eq!(fs::read(dir.join("t/bin/clawbot")).unwrap(), b"tar");
+        assert!(!dir.join(".t.extracting").exists());
+    }
+
+    #[test]
+    fn rejects_escaping_entries() {
+        let dir = temp_dir("extract-escape");
+        let zip_path = dir.join("slip.zip");
+        write_zip(&zip_path, &[("../evil", b"x")]);
+        assert!(extract_archive(&zip_path, &dir.join("z"), MAX_UNCOMPRESSED_BYTES).is_err());
+        assert!(!dir.join("evil").exists());
+        assert!(!dir.join("z").exists());
+
+        let tar_path = dir.join("abs.tar.gz");
+        write_tar_gz(&tar_path, |b| {
+            append_raw(b, "/tmp/evil", tar::EntryType::Regular, None, b"x");
+        });
+        assert!(extract_archive(&tar_path, &dir.join("t"), MAX_UNCOMPRESSED_BYTES).is_err());
+    }
+
+    #[test]
+    fn rejects_hardlinks_and_devices() {
+        let dir = temp_dir("extract-kinds");
+        use tar::EntryType::{Block, Char, Link};
+        for (i, kind) in [Link, Char, Block].into_iter().enumerate() {
+            let path = dir.join(format!("{}.tar.gz", i));
+            write_tar_gz(&path, |b| {
+                append_raw(b, "a", tar::EntryType::Regular, None, b"a");
+                append_raw(b, "b", kind, Some("a"), b"");
+            });
+            let out = dir.join(format!("out{}", i));
+            let err = extract_archive(&path, &out, MAX_UNCOMPRESSED_BYTES).unwrap_err();
+            assert!(err.contains("不支持的条目类型"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn rejects_oversized_archive() {
+        let dir = temp_dir("extract-bomb");
+        let path = dir.join("big.tar.gz");
+        write_tar_gz(&path, |b| {
+            append_raw(b, "big", tar::EntryType::Regular, None, &[0u8; 4096]);
+        });
+        let err = extract_archive(&path, &dir.join("out"), 1024).unwrap_err();
+        assert!(err.contains("上限"), "{}", err);
+        assert!(!dir.join("out").exists());
+    }
+}
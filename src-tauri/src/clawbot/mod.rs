@@ -0,0 +1,176 @@
+//! Clawbot 托管：下载、安装、启动 / 停止外部应用 Clawbot，设计见 `doc/clawbot-integration.md`。
+//!
+//! 目录（均在 app_data 下）：
+//!
+//! ```text
+//! clawbot/
+//! ├── releases/     # 下载的压缩包，见 [download]
+//! ├── versions/     # 各版本解压目录，见 [extract]；切换 / 回滚 / 清理见 [versions]
+//! └── state.json    # 本模块维护的状态，见 [ClawbotState]；currentVersion 即 current 指针
+//! ```
+//!
+//! 进程作为托管服务 `clawbot` 由监管器启动 / 停止，见 [service]。
+//!
+//! `state.json` 只记录持久信息；是否在运行等实时状态取自监管器，
+//! 二者在 [clawbot_check_state] 中合并后返回前端。
+
+pub mod download;
+pub mod extract;
+pub mod service;
+pub mod versions;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::util::now_secs;
+
+/// Clawbot 根目录名（位于 app_data 下）。
+const CLAWBOT_DIR_NAME: &str = "clawbot";
+const STATE_FILE_NAME: &str = "state.json";
+/// history 最多保留条数，超出时丢弃最旧的。
+const HISTORY_LIMIT: usize = 50;
+
+/// 安装 / 升级 / 启动等时间线中的一条记录。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    /// Unix 秒
+    pub at: u64,
+    /// 如 "download"、"install"、"start"、"stop"、"error"
+    pub action: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// 对应 `<APP_DATA_DIR>/clawbot/state.json`。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ClawbotState {
+    /// 当前生效版本；None 表示未安装。
+    pub current_version: Option<String>,
+    /// 当前版本生效（安装或切换）时间（Unix 秒）。
+    pub installed_at: Option<u64>,
+    /// 最近一次切换前的版本，供回滚。
+    pub rollback_to: Option<String>,
+    /// 最近一次启动时分配的端口。
+    pub port: Option<u16>,
+    /// 正在下载的版本，供前端展示；并发互斥见 [download] 中的 `DOWNLOADING`。
+    pub download_in_progress: Option<String>,
+    pub last_error: Option<String>,
+    pub history: Vec<HistoryEntry>,
+}
+
+impl ClawbotState {
+    /// 追加一条时间线记录并按 [HISTORY_LIMIT] 截断。
+    pub fn push_history(&mut self, action: &str, version: Option<&str>, detail: Option<String>) {
+        self.history.push(HistoryEntry {
+            at: now_secs(),
+            action: action.to_string(),
+            version: version.map(String::from),
+            detail,
+        });
+        if self.history.len() > HISTORY_LIMIT {
+            let overflow = self.history.len() - HISTORY_LIMIT;
+            self.history.drain(..overflow);
+        }
+    }
+}
+
+/// 版本号会作为目录名使用，只允许字母数字与 `.-_+`，且不能以 `.` 开头。
+pub(crate) fn check_version_name(version: &str) -> Result<(), String> {
+    let ok = !version.is_empty()
+        && !version.starts_with('.')
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '+'));
+    if ok {
+        Ok(())
+    } else {
+        Err(format!("非法的版本号: {}", version))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// 路径与 state.json 读写
+// ---------------------------------------------------------------------------
+
+/// `<APP_DATA_DIR>/clawbot`
+pub fn clawbot_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let app_data = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(app_data.join(CLAWBOT_DIR_NAME))
+}
+
+/// 读取 state.json；不存在或损坏时返回默认状态（损坏时打印原因）。
+pub fn read_state(dir: &Path) -> ClawbotState {
+    let path = dir.join(STATE_FILE_NAME);
+    let Ok(text) = fs::read_to_string(&path) else {
+        return ClawbotState::default();
+    };
+    serde_json::from_str(&text).unwrap_or_else(|e| {
+        log::warn!("state.json 解析失败，按未安装处理: {}", e);
+        ClawbotState::default()
+    })
+}
+
+/// 原子写入 state.json：先写临时文件再 rename，避免中断后留下半个文件。
+pub fn write_state(dir: &Path, state: &ClawbotState) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let text = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+    let tmp = dir.join(format!("{}.tmp", STATE_FILE_NAME));
+    fs::write(&tmp, text).map_err(|e| format!("写入 {} 失败: {}", tmp.display(), e))?;
+    fs::rename(&tmp, dir.join(STATE_FILE_NAME)).map_err(|e| e.to_string())
+}
+
+/// 读取 - 修改 - 写回 state.json，返回修改后的状态。
+pub fn update_state(dir: &Path, f: impl FnOnce(&mut ClawbotState)) -> Result<ClawbotState, String> {
+    let mut state = read_state(dir);
+    f(&mut state);
+    write_state(dir, &state)?;
+    Ok(state)
+}
+
+/// Setup 阶段调用：启动时不可能有下载在进行，清掉上次异常退出遗留的 `downloadInProgress`。
+pub fn reconcile_on_setup(app: &AppHandle) {
+    let Ok(dir) = clawbot_dir(app) else {
+        return;
+    };
+    let mut state = read_state(&dir);
+    if state.download_in_progress.take().is_some() {
+        if let Err(e) = write_state(&dir, &state) {
+            log::warn!("重置下载状态失败: {}", e);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// 命令
+// ---------------------------------------------------------------------------
+
+/// [clawbot_check_state] 的返回：持久状态 + 实时进程状态。
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClawbotStatus {
+    #[serde(flatten)]
+    pub state: ClawbotState,
+    pub installed: bool,
+    pub running: bool,
+    pub pid: Option<u32>,
+}
+
+/// 返回 state.json 内容并合并进程是否在运行，前端据此决定显示「下载 / 安装」还是「启动」。
+#[tauri::command]
+pub fn clawbot_check_state(app: AppHandle) -> Result<ClawbotStatus, String> {
+    let state = read_state(&clawbot_dir(&app)?);
+    let pid = service::running_pid(&app);
+    Ok(ClawbotStatus {
+        installed: state.current_version.is_some(),
+        running: pid.is_some(),
+        pid,
+        state,
+    })
+}
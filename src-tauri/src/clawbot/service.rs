@@ -0,0 +1,217 @@
+//! Clawbot 作为托管服务 `clawbot` 交给 [crate::services::supervisor] 启动 / 停止：
+//! PID 登记、Ctrl+C 清理、退出跟踪与日志（`service_logs`）都与 core 等服务共用同一套实现。
+//!
+//! 清单按 state.json 的当前版本生成（[register]），切换版本后须重新注册；
+//! 端口由监管器用 portpicker 分配，经环境变量 `CLAWBOT_PORT` / `PORT` 传入，并在启动后写回 state.json 的 `port`。
+//! stdout / stderr 另追加写入 `<app_log_dir>/clawbot.log`（超过 5 MiB 时在下次启动前轮转为 `clawbot.log.1`）。
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use super::extract::versions_dir;
+use super::{clawbot_dir, read_state, update_state};
+use crate::services::manifest::{PortSpec, PortStrategy, ServiceManifest};
+use crate::services::supervisor::{self, ServiceHooks, ServiceInfo, Supervisor};
+
+pub const CLAWBOT_SERVICE_NAME: &str = "clawbot";
+const LOG_FILE_NAME: &str = "clawbot.log";
+
+/// 版本目录下的入口可执行文件：`bin/clawbot`（Windows 为 `bin/clawbot.exe`）。
+pub fn entrypoint(version_dir: &Path) -> PathBuf {
+    let name = if cfg!(windows) {
+        "clawbot.exe"
+    } else {
+        "clawbot"
+    };
+    version_dir.join("bin").join(name)
+}
+
+/// `<app_log_dir>/clawbot.log`
+pub fn log_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_log_dir().map_err(|e| e.to_string())?;
+    Ok(dir.join(LOG_FILE_NAME))
+}
+
+fn clawbot_log_file(app: &AppHandle) -> Option<PathBuf> {
+    log_path(app).ok()
+}
+
+fn clawbot_manifest(version: &str, version_dir: &Path, exe: &Path) -> ServiceManifest {
+    ServiceManifest {
+        name: CLAWBOT_SERVICE_NAME.to_string(),
+        description: Some(format!("Clawbot {}", version)),
+        command: exe.to_string_lossy().to_string(),
+        sidecar: false,
+        args: Vec::new(),
+        env: [("PORT".to_string(), "${PORT}".to_string())].into(),
+        cwd: Some(version_dir.to_string_lossy().to_string()),
+        readiness: Default::default(),
+        port: PortSpec {
+            strategy: PortStrategy::Pick,
+            value: None,
+            env: Some("CLAWBOT_PORT".to_string()),
+        },
+        restart: Default::default(),
+        autostart: false,
+        depends_on: Vec::new(),
+        required_by: Vec::new(),
+    }
+}
+
+fn on_clawbot_started(app: &AppHandle, pid: u32, port: Option<u16>) {
+    let Ok(dir) = clawbot_dir(app) else {
+        return;
+    };
+    let result = update_state(&dir, |s| {
+        s.port = port;
+        let version = s.current_version.clone();
+        s.push_history(
+            "start",
+            version.as_deref(),
+            Some(format!("PID {} | 端口 {:?}", pid, port)),
+        );
+    });
+    if let Err(e) = result {
+        log::warn!("记录 Clawbot 启动失败: {}", e);
+    }
+}
+
+/// 按当前版本注册（或替换）`clawbot` 服务；未安装时移除注册并返回 false。
+/// 服务运行中时无法替换，调用方须先停止。
+pub fn register(app: &AppHandle) -> Result<bool, String> {
+    match read_state(&clawbot_dir(app)?).current_version {
+        Some(version) => register_version(app, &version).map(|_| true),
+        None => {
+            supervisor_state(app)?.unregister(CLAWBOT_SERVICE_NAME)?;
+            Ok(false)
+        }
+    }
+}
+
+fn register_version(app: &AppHandle, version: &str) -> Result<(), String> {
+    let version_dir = versions_dir(&clawbot_dir(app)?).join(version);
+    let exe = entrypoint(&version_dir);
+    if !exe.is_file() {
+        return Err(format!("未找到 Clawbot 入口: {}", exe.display()));
+    }
+    supervisor_state(app)?.register(
+        clawbot_manifest(version, &version_dir, &exe),
+        ServiceHooks {
+            on_started: Some(on_clawbot_started),
+            log_file: Some(clawbot_log_file),
+            ..Default::default()
+        },
+    )
+}
+
+fn supervisor_state(app: &AppHandle) -> Result<tauri::State<'_, Supervisor>, String> {
+    app.try_state::<Supervisor>()
+        .ok_or_else(|| "Supervisor 未注册".to_string())
+}
+
+/// Setup 阶段调用：已安装时注册服务（不自动启动），用户服务清单因此可以依赖 `clawbot`。
+pub fn register_on_setup(app: &AppHandle) {
+    if let Err(e) = register(app) {
+        log::warn!("注册 Clawbot 服务失败: {}", e);
+    }
+}
+
+/// 运行中的 Clawbot PID；未注册或未运行时为 None。
+pub fn running_pid(app: &AppHandle) -> Option<u32> {
+    app.try_state::<Supervisor>()?
+        .info(CLAWBOT_SERVICE_NAME)
+        .ok()?
+        .pid
+}
+
+/// [clawbot_status] 的返回值：取自监管器的 [ServiceInfo]，另附日志文件路径。
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessStatus {
+    pub running: bool,
+    pub pid: Option<u32>,
+    pub port: Option<u16>,
+    pub uptime_secs: Option<u64>,
+    /// 上一次运行的退出码（仍在运行时为 None）
+    pub exit_code: Option<i32>,
+    pub log_path: Option<PathBuf>,
+}
+
+/// 当前进程状态；未安装（服务未注册）时视为未运行。
+pub fn status(app: &AppHandle) -> ProcessStatus {
+    let info = app
+        .try_state::<Supervisor>()
+        .and_then(|sup| sup.info(CLAWBOT_SERVICE_NAME).ok());
+    ProcessStatus {
+        running: info.as_ref().is_some_and(|i| i.pid.is_some()),
+        pid: info.as_ref().and_then(|i| i.pid),
+        port: info.as_ref().and_then(|i| i.port),
+        uptime_secs: info.as_ref().and_then(|i| i.uptime_secs),
+        exit_code: info.as_ref().and_then(|i| i.exit_code),
+        log_path: log_path(app).ok(),
+    }
+}
+
+/// 停止运行中的 Clawbot，返回是否确实停止了一个进程。
+pub fn stop(app: &AppHandle) -> Result<bool, String> {
+    if running_pid(app).is_none() {
+        return Ok(false);
+    }
+    supervisor::stop(app, CLAWBOT_SERVICE_NAME)?;
+    if let Ok(dir) = clawbot_dir(app) {
+        let _ = update_state(&dir, |s| {
+            let version = s.current_version.clone();
+            s.push_history("stop", version.as_deref(), None);
+        });
+    }
+    Ok(true)
+}
+
+/// 按当前版本注册后启动；已在运行时直接返回当前状态。
+pub fn start(app: &AppHandle) -> Result<ServiceInfo, String> {
+    if running_pid(app).is_none() && !register(app)? {
+        return Err("Clawbot 未安装".to_string());
+    }
+    supervisor::start(app, CLAWBOT_SERVICE_NAME)
+}
+
+/// 以指定版本（不必是 state.json 中的当前版本）注册并启动，供切换版本时先确认新版本能启动。
+pub fn start_version(app: &AppHandle, version: &str) -> Result<ServiceInfo, String> {
+    register_version(app, version)?;
+    supervisor::start(app, CLAWBOT_SERVICE_NAME)
+}
+
+// ---------------------------------------------------------------------------
+// 命令
+// ---------------------------------------------------------------------------
+
+/// 启动 Clawbot（已在运行则不重复启动）；最近日志经 `service_logs("clawbot")` 查询，完整日志见 clawbot.log。
+#[tauri::command]
+pub async fn clawbot_start(app: AppHandle) -> Result<ServiceInfo, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        start(&app).inspect_err(|e| {
+            if let Ok(dir) = clawbot_dir(&app) {
+                let _ = update_state(&dir, |s| {
+                    s.last_error = Some(e.clone());
+                    s.push_history("error", None, Some(e.clone()));
+                });
+            }
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// 停止 Clawbot。
+#[tauri::command]
+pub fn clawbot_stop(app: AppHandle) -> Result<bool, String> {
+    stop(&app)
+}
+
+/// 返回 pid / 端口 / 运行时长 / 退出码。
+#[tauri::command]
+pub fn clawbot_status(app: AppHandle) -> ProcessStatus {
+    status(&app)
+}
@@ -0,0 +1,164 @@
+//! 多版本并存：`versions/<v>/` 各自独立，state.json 的 `currentVersion` 即「current」指针。
+//!
+//! - 切换（activate）时若 Clawbot 在运行，先用新版本重新拉起，成功后才原子写入 state.json 并清理，
+//!   失败则恢复原版本；切换前的版本记入 `rollbackTo`，[clawbot_rollback] 据此回退。
+//! - 保留策略：除当前版本与 `rollbackTo` 外，只保留最近安装的 [KEEP_VERSIONS] 个版本，
+//!   其余版本目录与 `releases/` 中对应压缩包一并删除。
+
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::download::releases_dir;
+use super::extract::versions_dir;
+use super::{check_version_name, clawbot_dir, read_state, service, update_state, ClawbotState};
+use crate::services::supervisor;
+use crate::util::now_secs;
+
+/// 保留的版本数量（不含当前版本与回滚版本）。
+pub const KEEP_VERSIONS: usize = 3;
+
+/// 已安装版本的描述。
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstalledVersion {
+    pub version: String,
+    /// 版本目录修改时间（Unix 秒），近似安装时间
+    pub installed_at: u64,
+    pub active: bool,
+    pub rollback_target: bool,
+    pub has_archive: bool,
+}
+
+/// 列出 `versions/` 下的全部版本（新安装的在前），跳过解压中的临时目录。
+pub fn list_installed(clawbot: &Path, state: &ClawbotState) -> Vec<InstalledVersion> {
+    let Ok(entries) = fs::read_dir(versions_dir(clawbot)) else {
+        return Vec::new();
+    };
+    let releases = releases_dir(clawbot);
+    let mut list: Vec<InstalledVersion> = entries
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| {
+            let version = e.file_name().to_string_lossy().to_string();
+            if version.starts_with('.') {
+                return None;
+            }
+            let installed_at = e
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            let has_archive = ["zip", "tar.gz"]
+                .iter()
+                .any(|ext| releases.join(format!("{}.{}", version, ext)).exists());
+            Some(InstalledVersion {
+                active: state.current_version.as_deref() == Some(version.as_str()),
+                rollback_target: state.rollback_to.as_deref() == Some(version.as_str()),
+                version,
+                installed_at,
+                has_archive,
+            })
+        })
+        .collect();
+    list.sort_by(|a, b| b.installed_at.cmp(&a.installed_at));
+    list
+}
+
+/// 按保留策略删除旧版本目录及其压缩包，返回被删除的版本。
+pub fn prune(clawbot: &Path) -> Vec<String> {
+    let state = read_state(clawbot);
+    let releases = releases_dir(clawbot);
+    let mut kept = 0;
+    let mut removed = Vec::new();
+    for v in list_installed(clawbot, &state) {
+        if v.active || v.rollback_target {
+            continue;
+        }
+        if kept < KEEP_VERSIONS {
+            kept += 1;
+            continue;
+        }
+        if let Err(e) = fs::remove_dir_all(versions_dir(clawbot).join(&v.version)) {
+            log::warn!("删除旧版本 {} 失败: {}", v.version, e);
+            continue;
+        }
+        for ext in ["zip", "tar.gz"] {
+            let _ = fs::remove_file(releases.join(format!("{}.{}", v.version, ext)));
+        }
+        removed.push(v.version);
+    }
+    removed
+}
+
+/// 把已解压的 `version` 设为当前版本。Clawbot 在运行时先停掉旧进程并用新版本启动，
+/// 启动成功后才写 state.json 并清理旧版本；启动失败则用原版本重新拉起，state.json 保持不变。
+pub fn activate(app: &AppHandle, version: &str) -> Result<ClawbotState, String> {
+    check_version_name(version)?;
+    let dir = clawbot_dir(app)?;
+    let version_dir = versions_dir(&dir).join(version);
+    if !service::entrypoint(&version_dir).is_file() {
+        return Err(format!("版本 {} 未安装或缺少入口", version));
+    }
+
+    let restarted = service::stop(app)?;
+    if restarted {
+        if let Err(e) = service::start_version(app, version) {
+            let _ = supervisor::stop(app, service::CLAWBOT_SERVICE_NAME);
+            if let Err(restore) = service::start(app) {
+                log::error!("恢复原版本失败: {}", restore);
+            }
+            return Err(format!("启动 {} 失败，已恢复原版本: {}", version, e));
+        }
+    }
+
+    let state = update_state(&dir, |s| {
+        if s.current_version.as_deref() != Some(version) {
+            s.rollback_to = s.current_version.take();
+        }
+        s.current_version = Some(version.to_string());
+        s.installed_at = Some(now_secs());
+        s.push_history("activate", Some(version), None);
+    })?;
+    // 未运行时同样让注册的清单跟随新版本，之后经 service_start 启动的也是新版本
+    if !restarted {
+        if let Err(e) = service::register(app) {
+            log::warn!("注册 Clawbot {} 失败: {}", version, e);
+        }
+    }
+
+    let removed = prune(&dir);
+    if !removed.is_empty() {
+        log::info!("按保留策略清理旧版本: {:?}", removed);
+    }
+    Ok(state)
+}
+
+/// 列出已安装的版本。
+#[tauri::command]
+pub fn clawbot_list_versions(app: AppHandle) -> Result<Vec<InstalledVersion>, String> {
+    let dir = clawbot_dir(&app)?;
+    let state = read_state(&dir);
+    Ok(list_installed(&dir, &state))
+}
+
+/// 切换到已安装的指定版本。
+#[tauri::command]
+pub fn clawbot_activate(app: AppHandle, version: String) -> Result<ClawbotState, String> {
+    activate(&app, &version)
+}
+
+/// 回退到上一次切换前的版本（state.json 的 `rollbackTo`）。
+#[tauri::command]
+pub fn clawbot_rollback(app: AppHandle) -> Result<ClawbotState, String> {
+    let dir = clawbot_dir(&app)?;
+    let target = read_state(&dir)
+        .rollback_to
+        .ok_or_else(|| "没有可回滚的版本".to_string())?;
+    activate(&app, &target)
+}
@@ -0,0 +1,466 @@
+//! 命令行子命令：在 Tauri 事件循环启动前解析，复用应用内相同的模块，
+//! 供管理员与支持人员在没有界面时检查、修复安装。
+//!
+//! ```text
+//! langchainapp [--profile <name>] config get <key> | config set <key> <value> | config path
+//! langchainapp [--profile <name>] store get <key> | store set <key> <value> [--ttl <秒>] | store keys | store export  [--path <store>]
+//! langchainapp [--profile <name>] db backup | db migrate | db check
+//! langchainapp [--profile <name>] core start | core status | core logs [--lines <n>] [--date YYYY-MM-DD]
+//! langchainapp [--profile <name>] paths
+//! ```
+//!
+//! - 结果以一行 JSON 打印到 stdout 的最后一行（`core logs` 打印原始日志行），错误打印到 stderr；
+//! - 退出码：0 成功，1 失败，2 用法错误，3 `core status` 时 core 未运行或不健康；
+//! - `<value>` 先按 JSON 解析，失败则视为字符串；
+//! - `--profile` 指定的 profile 须已存在，且不改变应用下次启动时使用的 profile；
+//! - `store set` 与 `db migrate` 在应用运行时拒绝执行（应用内的 store 缓存会覆盖写入，core 正在使用数据库）；
+//! - `core start` 等同于 `--headless` 启动，见 [crate::headless]。
+//!
+//! `open` / `run-script` 只在这里校验，随后照常启动应用并由单实例插件转发给已运行的实例，见 [instance]；
+//! 其他不是子命令的参数同样照常启动应用。
+
+use std::fs;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_http::reqwest;
+
+use crate::config;
+use crate::core;
+use crate::db::{self, backup::BackupReason, maintenance, migrate};
+use crate::headless;
+use crate::instance;
+use crate::logging;
+use crate::profile;
+use crate::services::supervisor::pid_alive;
+use crate::store;
+
+const EXIT_OK: i32 = 0;
+const EXIT_FAILED: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const EXIT_NOT_RUNNING: i32 = 3;
+const DEFAULT_LOG_LINES: usize = 200;
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(2);
+/// 作为子命令识别的第一个位置参数；其余参数照常启动应用。
+const SUBCOMMANDS: &[&str] = &["config", "store", "db", "core", "paths", "help"];
+/// 不带值的全局参数（`--profile` 带值，由 [profile::cli_profile] 读取）。
+const GLOBAL_FLAGS: &[&str] = &[headless::CLI_FLAG];
+
+const USAGE: &str = "用法: langchainapp [--profile <name>] <命令>
+
+  config get <key>                读取配置中的一项（settings.json 合并本机覆盖后）
+  config set <key> <value>        在本机覆盖文件中设置一项（value 先按 JSON 解析）
+  config path                     settings.json 与本机覆盖文件路径
+  store get <key> [--path <p>]    读取 store（默认为配置中的 store_name）
+  store set <key> <value> [--path <p>] [--ttl <秒>]
+  store keys [--path <p>]         列出 store 中的 key
+  store export [--path <p>]       导出 store（不带 --path 时导出全部）
+  db backup                       立即备份数据库
+  db migrate                      执行待执行的数据库迁移
+  db check                        完整性检查与迁移状态
+  core start                      以无窗口模式启动（等同于 --headless）
+  core status                     运行中的 core 的 PID、端口与健康状态
+  core logs [--lines <n>] [--date YYYY-MM-DD]
+  paths                           应用使用的各目录与文件";
+
+enum Command {
+    ConfigGet {
+        key: String,
+    },
+    ConfigSet {
+        key: String,
+        value: Value,
+    },
+    ConfigPath,
+    StoreGet {
+        path: Option<String>,
+        key: String,
+    },
+    StoreSet {
+        path: Option<String>,
+        key: String,
+        value: Value,
+        ttl_secs: Option<u64>,
+    },
+    StoreKeys {
+        path: Option<String>,
+    },
+    StoreExport {
+        path: Option<String>,
+    },
+    DbBackup,
+    DbMigrate,
+    DbCheck,
+    CoreStart,
+    CoreStatus,
+    CoreLogs {
+        lines: usize,
+        date: Option<String>,
+    },
+    Paths,
+    Help,
+}
+
+/// 位置参数与 `--name value` / `--name=value` 形式的选项。
+struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, String)>,
+}
+
+impl Args {
+    fn parse(mut raw: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut args = Args {
+            positional: Vec::new(),
+            options: Vec::new(),
+        };
+        while let Some(arg) = raw.next() {
+            if GLOBAL_FLAGS.contains(&arg.as_str()) {
+                continue;
+            }
+            let Some(name) = arg.strip_prefix("--") else {
+                args.positional.push(arg);
+                continue;
+            };
+            let (name, value) = match name.split_once('=') {
+                Some((n, v)) => (n.to_string(), v.to_string()),
+                None => {
+                    let value = raw
+                        .next()
+                        .ok_or_else(|| format!("选项 --{} 缺少值", name))?;
+                    (name.to_string(), value)
+                }
+            };
+            args.options.push((name, value));
+        }
+        Ok(args)
+    }
+
+    fn take(&mut self, name: &str) -> Option<String> {
+        let i = self.options.iter().position(|(n, _)| n == name)?;
+        Some(self.options.remove(i).1)
+    }
+
+    fn take_parsed<T: std::str::FromStr>(&mut self, name: &str) -> Result<Option<T>, String> {
+        self.take(name)
+            .map(|v| v.parse().map_err(|_| format!("--{} 的值无效: {}", name, v)))
+            .transpose()
+    }
+}
+
+/// 先按 JSON 解析，失败则视为字符串（`config set name foo` 无需写成 `"foo"`）。
+fn parse_value(raw: &str) -> Value {
+    serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+fn parse(mut args: Args) -> Result<Command, String> {
+    let positional = std::mem::take(&mut args.positional);
+    let words: Vec<&str> = positional.iter().map(String::as_str).collect();
+    let command = match words.as_slice() {
+        ["help"] => Command::Help,
+        ["config", "get", key] => Command::ConfigGet {
+            key: key.to_string(),
+        },
+        ["config", "set", key, value] => Command::ConfigSet {
+            key: key.to_string(),
+            value: parse_value(value),
+        },
+        ["config", "path"] => Command::ConfigPath,
+        ["store", "get", key] => Command::StoreGet {
+            path: args.take("path"),
+            key: key.to_string(),
+        },
+        ["store", "set", key, value] => Command::StoreSet {
+            path: args.take("path"),
+            key: key.to_string(),
+            value: parse_value(value),
+            ttl_secs: args.take_parsed("ttl")?,
+        },
+        ["store", "keys"] => Command::StoreKeys {
+            path: args.take("path"),
+        },
+        ["store", "export"] => Command::StoreExport {
+            path: args.take("path"),
+        },
+        ["db", "backup"] => Command::DbBackup,
+        ["db", "migrate"] => Command::DbMigrate,
+        ["db", "check"] => Command::DbCheck,
+        ["core", "start"] => Command::CoreStart,
+        ["core", "status"] => Command::CoreStatus,
+        ["core", "logs"] => Command::CoreLogs {
+            lines: args.take_parsed("lines")?.unwrap_or(DEFAULT_LOG_LINES),
+            date: args.take("date"),
+        },
+        ["paths"] => Command::Paths,
+        _ => return Err(format!("未知命令: {}", words.join(" "))),
+    };
+    // --profile 由 profile 模块自行读取
+    args.take("profile");
+    if let Some((name, _)) = args.options.first() {
+        return Err(format!("未知选项: --{}", name));
+    }
+    Ok(command)
+}
+
+/// 在 [crate::run] 之前调用：第一个位置参数是子命令时执行并返回退出码，否则返回 None。
+/// 转发给主实例的命令（见 [instance]）在这里先校验，不合法时直接返回退出码。
+pub fn run() -> Option<i32> {
+    let raw: Vec<String> = std::env::args().skip(1).collect();
+    if instance::is_command(&raw)
+        || headless::is_enabled()
+        || raw.iter().any(|w| SUBCOMMANDS.contains(&w.as_str()))
+    {
+        attach_console();
+    }
+    if instance::is_command(&raw) {
+        let cwd = std::env::current_dir().unwrap_or_default();
+        return instance::parse(&raw, &cwd).err().map(|e| {
+            eprintln!("[cli] {}", e);
+            EXIT_FAILED
+        });
+    }
+    let args = Args::parse(raw.iter().cloned());
+    let is_subcommand = match &args {
+        Ok(a) => a
+            .positional
+            .first()
+            .is_some_and(|w| SUBCOMMANDS.contains(&w.as_str())),
+        // 选项缺值时只有在子命令里才算用法错误
+        Err(_) => raw.iter().any(|w| SUBCOMMANDS.contains(&w.as_str())),
+    };
+    if !is_subcommand {
+        return None;
+    }
+    let command = match args.and_then(parse) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return Some(EXIT_USAGE);
+        }
+    };
+    Some(match command {
+        Command::Help => {
+            println!("{}", USAGE);
+            EXIT_OK
+        }
+        Command::CoreStart => {
+            headless::enable();
+            crate::run();
+            EXIT_OK
+        }
+        command => match build_app().and_then(|app| execute(app.handle(), command)) {
+            Ok(code) => code,
+            Err(e) => {
+                eprintln!("[cli] {}", e);
+                EXIT_FAILED
+            }
+        },
+    })
+}
+
+/// Windows 发布版为 GUI 子系统，不带控制台：从终端运行子命令或 `--headless` 时接上父进程的控制台，
+/// 让 stdout / stderr 输出可见。没有父控制台（如双击启动）或输出已重定向时不受影响。
+#[cfg(windows)]
+fn attach_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    // SAFETY: 只调用 Win32 API，失败时返回 0，无需处理
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+/// 只加载 store 插件、不创建窗口、不运行事件循环的应用，用来解析目录与读取资源。
+fn build_app() -> Result<tauri::App, String> {
+    // 模块日志只写 stderr，stdout 留给命令结果
+    logging::init_for_cli();
+    #[allow(unused_mut)]
+    let mut app = tauri::Builder::default()
+        .plugin(tauri_plugin_store::Builder::new().build())
+        .build(crate::context(false))
+        .map_err(|e| e.to_string())?;
+    #[cfg(target_os = "macos")]
+    app.set_activation_policy(tauri::ActivationPolicy::Accessory);
+    app.manage(store::schema::StoreSchemas::load(app.handle()));
+    profile::init_for_cli(app.handle())?;
+    Ok(app)
+}
+
+fn print_json(value: &Value) {
+    println!("{}", value);
+}
+
+fn to_json(value: impl serde::Serialize) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| e.to_string())
+}
+
+fn store_path(app: &AppHandle, path: Option<String>) -> String {
+    path.unwrap_or_else(|| config::get_store_name(app))
+}
+
+/// 应用（本 profile）仍在运行时拒绝执行会与它冲突的写操作。
+fn ensure_app_stopped(app: &AppHandle) -> Result<(), String> {
+    match core::read_runtime(app) {
+        Some(r) if r.app_pid != std::process::id() && pid_alive(r.app_pid) => Err(format!(
+            "应用正在运行（profile {}，PID {}），请先退出应用再执行",
+            r.profile, r.app_pid
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn execute(app: &AppHandle, command: Command) -> Result<i32, String> {
+    match command {
+        Command::ConfigGet { key } => {
+            let value = config::load_config_json(app)
+                .get(&key)
+                .cloned()
+                .ok_or_else(|| format!("配置中没有 {}", key))?;
+            print_json(&value);
+        }
+        Command::ConfigSet { key, value } => {
+            config::set_value(app, &key, value.clone())?;
+            print_json(&json!({ key: value }));
+        }
+        Command::ConfigPath => print_json(&json!({
+            "settings": config::settings_path(app)?,
+            "overrides": config::overrides_path(app)?,
+        })),
+        Command::StoreGet { path, key } => {
+            let value = store::store_read(app.clone(), store_path(app, path), key)?;
+            print_json(&value.unwrap_or(Value::Null));
+        }
+        Command::StoreSet {
+            path,
+            key,
+            value,
+            ttl_secs,
+        } => {
+            ensure_app_stopped(app)?;
+            store::store_write(app.clone(), store_path(app, path), key, value, ttl_secs)
+                .map_err(|e| to_json(e).map(|v| v.to_string()).unwrap_or_default())?;
+            print_json(&json!({ "ok": true }));
+        }
+        Command::StoreKeys { path } => {
+            print_json(&to_json(store::keys(app, &store_path(app, path))?)?)
+        }
+        Command::StoreExport { path } => {
+            print_json(&to_json(store::backup::store_export(app.clone(), path)?)?)
+        }
+        Command::DbBackup => {
+            let info = db::backup::backup_now(app, BackupReason::Manual)?.ok_or_else(|| {
+                format!(
+                    "数据库不存在: {}",
+                    db::db_path(app).unwrap_or_default().display()
+                )
+            })?;
+            print_json(&to_json(info)?);
+        }
+        Command::DbMigrate => {
+            ensure_app_stopped(app)?;
+            let applied = migrate::migrate(app)?;
+            print_json(&json!({ "applied": applied }));
+        }
+        Command::DbCheck => return db_check(app),
+        Command::CoreStatus => return core_status(app),
+        Command::CoreLogs { lines, date } => core_logs(app, lines, date)?,
+        Command::Paths => print_json(&paths(app)?),
+        Command::Help | Command::CoreStart => unreachable!("在 run 中处理"),
+    }
+    Ok(EXIT_OK)
+}
+
+/// 完整性检查 + 迁移状态；损坏或迁移记录与文件不一致时退出码为 1。
+fn db_check(app: &AppHandle) -> Result<i32, String> {
+    let (conn, path) = maintenance::open_existing(app)?;
+    let integrity =
+        maintenance::integrity_check(&conn, &maintenance::Reporter::new("integrity_check", None))?;
+    let files = migrate::load_files(&migrate::migrations_dir(app)?)?;
+    let migrations = migrate::status(&conn, &files)?;
+    let drifted = migrations.iter().any(|m| m.drifted);
+    let pending = migrations.iter().filter(|m| m.applied_at.is_none()).count();
+    let ok = integrity.ok && !drifted;
+    print_json(&json!({
+        "ok": ok,
+        "path": path,
+        "integrity": integrity,
+        "pendingMigrations": pending,
+        "migrations": migrations,
+    }));
+    Ok(if ok { EXIT_OK } else { EXIT_FAILED })
+}
+
+/// 读取运行记录，检查进程是否存活并请求 `/health`。
+fn core_status(app: &AppHandle) -> Result<i32, String> {
+    let Some(runtime) = core::read_runtime(app) else {
+        print_json(&json!({ "running": false, "profile": profile::active() }));
+        return Ok(EXIT_NOT_RUNNING);
+    };
+    let running = pid_alive(runtime.app_pid) && pid_alive(runtime.pid);
+    let url = format!("http://127.0.0.1:{}/health", runtime.port);
+    let healthy = running
+        && tauri::async_runtime::block_on(async {
+            reqwest::Client::new()
+                .get(&url)
+                .timeout(HEALTH_TIMEOUT)
+                .send()
+                .await
+                .is_ok_and(|r| r.status().is_success())
+        });
+    let mut status = to_json(&runtime)?;
+    if let Value::Object(m) = &mut status {
+        m.insert("running".into(), running.into());
+        m.insert("healthy".into(), healthy.into());
+    }
+    print_json(&status);
+    Ok(if healthy { EXIT_OK } else { EXIT_NOT_RUNNING })
+}
+
+/// 打印 core 某一天（默认最近一天）日志的最后 `lines` 行。
+fn core_logs(app: &AppHandle, lines: usize, date: Option<String>) -> Result<(), String> {
+    let files = core::daily_log_files(app)?;
+    let (_, path) = match &date {
+        Some(d) => files
+            .iter()
+            .find(|(day, _)| day == d)
+            .ok_or_else(|| format!("没有 {} 的 core 日志", d))?,
+        None => files.last().ok_or_else(|| {
+            format!(
+                "{} 下没有 core 日志",
+                core::log_base_path(app)
+                    .ok()
+                    .and_then(|p| p.parent().map(|d| d.display().to_string()))
+                    .unwrap_or_default()
+            )
+        })?,
+    };
+    let text =
+        fs::read_to_string(path).map_err(|e| format!("读取 {} 失败: {}", path.display(), e))?;
+    let all: Vec<&str> = text.lines().collect();
+    for line in &all[all.len().saturating_sub(lines)..] {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+fn paths(app: &AppHandle) -> Result<Value, String> {
+    let p = app.path();
+    let data_dir = profile::data_dir(app)?;
+    Ok(json!({
+        "appData": p.app_data_dir().ok(),
+        "appConfig": p.app_config_dir().ok(),
+        "appLog": p.app_log_dir().ok(),
+        "resource": p.resource_dir().ok(),
+        "profile": profile::active(),
+        "profileData": data_dir,
+        "database": db::db_path(app).ok(),
+        "store": data_dir.join(config::get_store_name(app)),
+        "settings": config::settings_path(app).ok(),
+        "settingsOverrides": config::overrides_path(app).ok(),
+        "coreLog": core::log_base_path(app).ok(),
+        "dbBackups": db::backup::backup_dir(app).ok(),
+        "migrations": migrate::migrations_dir(app).ok(),
+    }))
+}
@@ -1,47 +1,100 @@
-//! 配置：读 settings.json 文本，缺键补占位默认（数字 0、字符串 ""）后当 JSON 发给前端。
+//! 配置：读 settings.json 文本，合并本机覆盖（见 [overrides_path]），缺键补占位默认（数字 0、字符串 ""、布尔 false）
+//! 后当 JSON 发给前端。
 //! 各 getter 在取到时再做「保证运行」的 fallback，避免与真实配置歧义。
 
+use std::path::{Path, PathBuf};
+
 use serde_json::{Map, Number, Value};
 use tauri::path::BaseDirectory;
 use tauri::{AppHandle, Manager};
 
+use crate::store::migrate::write_json_atomic;
+
 const SETTINGS_RESOURCE_PATH: &str = "config/settings.json";
+const OVERRIDES_FILE_NAME: &str = "settings.override.json";
 
-/// 占位默认：数字 0、字符串 ""，仅表示「未配置」，不做业务含义。
+/// 占位默认：数字 0、字符串 ""、布尔 false，仅表示「未配置」，不做业务含义。
 fn default_json() -> Value {
     let mut m = Map::new();
     m.insert("sqlite_db_name".into(), Value::String(String::new()));
     m.insert("api_port".into(), Value::Number(Number::from(0)));
     m.insert("store_name".into(), Value::String(String::new()));
+    m.insert("store_encrypt".into(), Value::Bool(false));
+    m.insert("clawbot_manifest_url".into(), Value::String(String::new()));
+    m.insert("core_update_manifest_url".into(), Value::String(String::new()));
+    m.insert("core_update_public_key".into(), Value::String(String::new()));
+    m.insert("core_integrity_policy".into(), Value::String(String::new()));
+    m.insert("db_backup_interval_hours".into(), Value::Number(Number::from(0)));
+    m.insert("db_backup_keep".into(), Value::Number(Number::from(0)));
+    m.insert("db_backup_max_age_days".into(), Value::Number(Number::from(0)));
+    m.insert("log_level".into(), Value::String(String::new()));
     Value::Object(m)
 }
 
+/// 资源目录下的 settings.json 路径。
+pub fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .resolve(SETTINGS_RESOURCE_PATH, BaseDirectory::Resource)
+        .map_err(|e| e.to_string())
+}
+
+/// 本机覆盖配置：`<app_config_dir>/settings.override.json`，由 `config set` 写入，
+/// 逐键覆盖资源目录下的 settings.json（后者只读，且随应用更新被替换）。
+pub fn overrides_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+    Ok(dir.join(OVERRIDES_FILE_NAME))
+}
+
+/// 读取 JSON 对象；文件不存在或不是对象时返回 None。
+fn read_object(path: &Path) -> Option<Map<String, Value>> {
+    let s = std::fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&s) {
+        Ok(Value::Object(m)) => Some(m),
+        _ => None,
+    }
+}
+
 /// 供 get_config 命令与内部使用；仅读文件，不合并 runtime 端口。
+/// settings.json 之上合并 [overrides_path]，缺键补占位默认。
 pub fn load_config_json(app: &AppHandle) -> Value {
-    let path = match app
-        .path()
-        .resolve(SETTINGS_RESOURCE_PATH, BaseDirectory::Resource)
-    {
-        Ok(p) => p,
-        Err(_) => return default_json(),
-    };
-    let s = match std::fs::read_to_string(&path) {
-        Ok(x) => x,
-        Err(_) => return default_json(),
-    };
-    let mut obj: Map<String, Value> = match serde_json::from_str(&s) {
-        Ok(Value::Object(m)) => m,
-        _ => return default_json(),
-    };
-    obj.entry("sqlite_db_name")
-        .or_insert_with(|| Value::String(String::new()));
-    obj.entry("api_port")
-        .or_insert_with(|| Value::Number(Number::from(0)));
-    obj.entry("store_name")
-        .or_insert_with(|| Value::String(String::new()));
+    let mut obj = settings_path(app)
+        .ok()
+        .and_then(|p| read_object(&p))
+        .unwrap_or_default();
+    if let Some(overrides) = overrides_path(app).ok().and_then(|p| read_object(&p)) {
+        obj.extend(overrides);
+    }
+    if let Value::Object(defaults) = default_json() {
+        for (key, value) in defaults {
+            obj.entry(key).or_insert(value);
+        }
+    }
     Value::Object(obj)
 }
 
+/// 供 CLI 使用：在 [overrides_path] 中设置一项并原子写回，不修改资源目录下的 settings.json。
+/// 已知键须与占位默认同类型（数字 / 字符串 / 布尔），未知键原样写入；文件不存在或不是对象时从空对象开始。
+pub fn set_value(app: &AppHandle, key: &str, value: Value) -> Result<(), String> {
+    if let Some(default) = default_json().get(key) {
+        let same_kind = matches!(
+            (default, &value),
+            (Value::Number(_), Value::Number(_))
+                | (Value::String(_), Value::String(_))
+                | (Value::Bool(_), Value::Bool(_))
+        );
+        if !same_kind {
+            return Err(format!("{} 的值类型应与默认值 {} 相同", key, default));
+        }
+    }
+    let path = overrides_path(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let mut obj = read_object(&path).unwrap_or_default();
+    obj.insert(key.to_string(), value);
+    write_json_atomic(&path, &Value::Object(obj))
+}
+
 /// 供 sidecar 等内部使用。配置为空时 fallback 为 app.db 以保证运行。
 pub fn get_sqlite_db_name(app: &AppHandle) -> String {
     load_config_json(app)
@@ -71,3 +124,71 @@ pub fn get_store_name(app: &AppHandle) -> String {
         .map(String::from)
         .unwrap_or_else(|| "store.json".to_string())
 }
+
+/// 供 store 使用：是否以加密模式读写 Tauri Store。缺失或非布尔时视为 false。
+pub fn get_store_encrypt(app: &AppHandle) -> bool {
+    load_config_json(app)
+        .get("store_encrypt")
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// 供 clawbot 下载使用：发布清单地址（http(s):// 或 file://）。未配置时返回 None，不做 fallback。
+pub fn get_clawbot_manifest_url(app: &AppHandle) -> Option<String> {
+    non_empty_str(app, "clawbot_manifest_url")
+}
+
+/// 供 core 更新使用：更新清单地址（http(s):// 或 file://）。未配置时返回 None，不检查更新。
+pub fn get_core_update_manifest_url(app: &AppHandle) -> Option<String> {
+    non_empty_str(app, "core_update_manifest_url")
+}
+
+/// 供 core 更新使用：Ed25519 公钥（base64），更新包必须带有效签名。未配置时只允许 `file://` 清单（仅校验 SHA-256）。
+pub fn get_core_update_public_key(app: &AppHandle) -> Option<String> {
+    non_empty_str(app, "core_update_public_key")
+}
+
+/// 供 core 完整性校验使用："enforce" | "warn" | "off"。未配置或无法识别时返回 None，
+/// 由调用方按构建类型决定（release 拒绝、debug 警告）。
+pub fn get_core_integrity_policy(app: &AppHandle) -> Option<String> {
+    non_empty_str(app, "core_integrity_policy")
+        .map(|s| s.to_ascii_lowercase())
+        .filter(|s| matches!(s.as_str(), "enforce" | "warn" | "off"))
+}
+
+/// 供数据库备份使用：定时备份间隔（小时）。配置为 0 或缺失时 fallback 为 24。
+pub fn get_db_backup_interval_hours(app: &AppHandle) -> u64 {
+    positive_u64(app, "db_backup_interval_hours").unwrap_or(24)
+}
+
+/// 供数据库备份使用：最多保留的备份份数。配置为 0 或缺失时 fallback 为 10。
+pub fn get_db_backup_keep(app: &AppHandle) -> usize {
+    positive_u64(app, "db_backup_keep").unwrap_or(10) as usize
+}
+
+/// 供数据库备份使用：备份最长保留天数。配置为 0 或缺失时 fallback 为 30。
+pub fn get_db_backup_max_age_days(app: &AppHandle) -> u64 {
+    positive_u64(app, "db_backup_max_age_days").unwrap_or(30)
+}
+
+/// 供日志使用：级别名（trace / debug / info / warn / error / silent）。未配置时返回 None，
+/// 由 [crate::logging] 决定默认值；环境变量 `LOG_LEVEL` 优先。
+pub fn get_log_level(app: &AppHandle) -> Option<String> {
+    non_empty_str(app, "log_level")
+}
+
+fn positive_u64(app: &AppHandle, key: &str) -> Option<u64> {
+    load_config_json(app)
+        .get(key)
+        .and_then(Value::as_u64)
+        .filter(|&n| n != 0)
+}
+
+fn non_empty_str(app: &AppHandle, key: &str) -> Option<String> {
+    load_config_json(app)
+        .get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+}
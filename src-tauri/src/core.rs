@@ -3,22 +3,49 @@
 //! ## 正常流程
 //! 1. 解析 core 目录（打包后 resource_dir，开发时 target/…/resources/core）。
 //! 2. 分配端口，构造环境变量（API_PORT、APP_DATA_DIR 等）。
-//! 3. 通过 `app.shell().sidecar("toolbox_node")` 启动：`toolbox_node index.js`，环境变量与工作目录注入。
+//! 3. 把 `toolbox_node index.js` 注册为托管服务 [CORE_SERVICE_NAME]，由 [crate::services::supervisor]
+//!    按依赖顺序启动（用户服务可用 `requiredBy: ["core"]` 让 core 等它就绪）；
+//!    stdout 出现 `###CORE_READY###` 即就绪，随后 emit `core-ready`。
+//!
+//! 已通过 [crate::updater] 下载并启用的 core 包 / Node 运行时优先于内置版本；
+//! 它们未能就绪时自动回退到内置版本重新启动。
 //!
 //! ## 跳过侧车时（TAURI_SKIP_SIDECAR=1 或未找到 core/侧车）
 //! 仅向 `core/.env` 写入与 [build_core_env] 一致的内容，供本地自启 core 使用。
 
 use std::fs;
 use std::io::Write;
-use std::sync::{Mutex, OnceLock};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Emitter, Manager};
-use tauri_plugin_shell::process::{CommandChild, CommandEvent};
-use tauri_plugin_shell::ShellExt;
 
 use crate::config;
-
-/// 侧车 PID 的全局副本，用于 Ctrl+C 时在信号处理里按 PID 终止（Drop 可能来不及执行）。
-static SIDECAR_PID: OnceLock<Mutex<Option<u32>>> = OnceLock::new();
+use crate::db;
+use crate::headless;
+use crate::integrity;
+use crate::logging;
+use crate::profile;
+use crate::services::manifest::{
+    PortSpec, PortStrategy, ReadinessSpec, RestartSpec, ServiceManifest,
+};
+use crate::services::supervisor::{self, FailureKind, ServiceHooks, Supervisor};
+use crate::services::user;
+use crate::store;
+use crate::updater;
+
+/// core 在托管服务表中的名字。
+pub const CORE_SERVICE_NAME: &str = "core";
+/// core 启动完成后在 stdout 打印的就绪标记。
+const CORE_READY_MARKER: &str = "###CORE_READY###";
+/// core 就绪超时（秒）；macOS 首次运行可能因 Gatekeeper 较慢，放宽到 2 分钟。
+const CORE_READY_TIMEOUT_SECS: u64 = 120;
+/// 运行记录文件名，位于 profile 数据目录，见 [CoreRuntime]。
+const RUNTIME_FILE_NAME: &str = "core-runtime.json";
+/// core 日志文件名（未设置 LOG_PATH 时），与 core/src/utils/logger.ts 一致。
+const CORE_LOG_FILE_NAME: &str = "langchain-serve.log";
 
 /// macOS: 移除侧车二进制文件的隔离属性，避免 Gatekeeper 首次运行时 10+ 秒延迟
 #[cfg(target_os = "macos")]
@@ -44,56 +71,6 @@ pub struct CorePorts {
     pub api_port: Mutex<Option<u16>>,
 }
 
-/// 保存 Node 侧车子进程句柄，应用退出时（Drop）自动 kill，避免残留进程。
-pub struct CoreSidecarChild(pub Mutex<Option<CommandChild>>);
-
-impl Default for CoreSidecarChild {
-    fn default() -> Self {
-        Self(Mutex::new(None))
-    }
-}
-
-impl Drop for CoreSidecarChild {
-    fn drop(&mut self) {
-        if let Ok(mut guard) = self.0.lock() {
-            if let Some(child) = guard.take() {
-                if let Err(e) = child.kill() {
-                    eprintln!("[core] 关闭 Node 侧车进程失败: {}", e);
-                } else {
-                    eprintln!("[core] 已关闭 Node 侧车进程");
-                }
-            }
-        }
-        if let Some(m) = SIDECAR_PID.get() {
-            if let Ok(mut g) = m.lock() {
-                *g = None;
-            }
-        }
-    }
-}
-
-/// 按 PID 终止侧车（供 Ctrl+C 等信号处理使用，此时 Drop 可能不会执行）。
-pub fn kill_sidecar_by_pid() {
-    let pid = SIDECAR_PID
-        .get()
-        .and_then(|m| m.lock().ok())
-        .and_then(|mut g| g.take());
-    if let Some(pid) = pid {
-        #[cfg(windows)]
-        {
-            let _ = std::process::Command::new("taskkill")
-                .args(["/PID", &pid.to_string(), "/F"])
-                .status();
-        }
-        #[cfg(not(windows))]
-        {
-            let _ = std::process::Command::new("kill")
-                .args(["-9", &pid.to_string()])
-                .status();
-        }
-    }
-}
-
 fn is_dev() -> bool {
     cfg!(debug_assertions)
 }
@@ -102,11 +79,20 @@ fn is_dev() -> bool {
 // 环境变量（与 core/.env 一致）
 // ---------------------------------------------------------------------------
 
-/// 构造 core 进程环境变量：API_PORT；若有 app_data 则加 APP_DATA_DIR、SQLITE_DB_PATH、DB_PATH、STORE_PATH。
+/// 构造 core 进程环境变量：API_PORT + [core_data_env]。
 pub fn build_core_env(app: &AppHandle, api_port: u16) -> Vec<(String, String)> {
     let mut env: Vec<(String, String)> = vec![("API_PORT".to_string(), api_port.to_string())];
+    env.extend(core_data_env(app));
+    env
+}
+
+/// 与 API 端口无关的部分：当前 profile 数据目录（见 [profile::data_dir]）下的
+/// APP_DATA_DIR、SQLITE_DB_PATH、DB_PATH、STORE_PATH，
+/// 另加已知的用户服务端口（见 [crate::services::user::port_env]）与无窗口模式的 API_TOKEN。
+fn core_data_env(app: &AppHandle) -> Vec<(String, String)> {
+    let mut env: Vec<(String, String)> = Vec::new();
 
-    if let Ok(app_data) = app.path().app_data_dir() {
+    if let Ok(app_data) = profile::data_dir(app) {
         let sql_name = config::get_sqlite_db_name(app);
         let store_name = config::get_store_name(app);
         let db_path = app_data.join(&sql_name);
@@ -125,12 +111,27 @@ pub fn build_core_env(app: &AppHandle, api_port: u16) -> Vec<(String, String)> {
             store_path.to_string_lossy().to_string(),
         ));
     }
+    // store 加密时 core 用该密钥解密 STORE_PATH 中的信封
+    if let Some(key) = store::core_store_key(app) {
+        env.push(("STORE_KEY".to_string(), key));
+    }
+    // 用户服务端口：SERVICE_<NAME>_PORT
+    env.extend(user::port_env(app));
+    if let Some(token) = headless::token() {
+        env.push(("API_TOKEN".to_string(), token.to_string()));
+    }
+    // core 的 pino 与 Rust 侧使用同一级别，便于 logs_query 合并查看
+    env.push(("LOG_LEVEL".to_string(), logging::level_name()));
 
     env
 }
 
+/// 只经子进程环境传给 core、不写入 core/.env 的变量（store 派生密钥与无窗口模式的令牌）。
+const SECRET_ENV_KEYS: &[&str] = &["STORE_KEY", "API_TOKEN"];
+
 fn env_to_dotenv_lines(env: &[(String, String)]) -> String {
     env.iter()
+        .filter(|(k, _)| !SECRET_ENV_KEYS.contains(&k.as_str()))
         .map(|(k, v)| format!("{}={}", k, v))
         .collect::<Vec<_>>()
         .join("\n")
@@ -157,10 +158,16 @@ fn core_src_dir() -> Option<std::path::PathBuf> {
 }
 
 /// 解析 **运行时** core 目录（含 index.js）。
-/// 优先级：resource_dir → target/debug|release/resources/core → src-tauri/resources/core。
+/// 优先级：已启用的更新包（见 [updater::preferred_core_dir]）→ resource_dir → target/debug|release/resources/core → src-tauri/resources/core。
 fn resolve_core_dir(app: &AppHandle) -> Option<(std::path::PathBuf, std::path::PathBuf)> {
     let manifest = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
 
+    if let Some(core) = updater::preferred_core_dir(app) {
+        if let Some(parent) = core.parent() {
+            return Some((parent.to_path_buf(), core));
+        }
+    }
+
     if let Ok(res) = app.path().resource_dir() {
         if res.exists() {
             let core = res.join("core");
@@ -194,12 +201,136 @@ fn resolve_core_dir(app: &AppHandle) -> Option<(std::path::PathBuf, std::path::P
 // 侧车启动
 // ---------------------------------------------------------------------------
 
-/// Setup 阶段调用：若未跳过侧车，则用 Node 侧车启动 resources/core/index.js。
-pub fn start_core_on_setup(app: &AppHandle) -> Result<(), String> {
+/// core 的托管服务清单：端口由监管器分配并以 API_PORT 传入，其余环境变量在每次 spawn 时
+/// 由 [core_data_env] 生成（依赖服务的端口要等它们启动后才知道）。
+/// `node` 为已启用的更新 Node 运行时；None 时使用内置侧车 `toolbox_node`。
+fn core_manifest(core_dir: &Path, index_js: &Path, node: Option<&Path>) -> ServiceManifest {
+    let command = node
+        .map(|bin| bin.to_string_lossy().to_string())
+        .unwrap_or_else(|| "toolbox_node".to_string());
+    ServiceManifest {
+        name: CORE_SERVICE_NAME.to_string(),
+        description: Some("内置 Node core 服务".to_string()),
+        command,
+        sidecar: node.is_none(),
+        args: vec![index_js.to_string_lossy().to_string()],
+        env: Default::default(),
+        cwd: Some(core_dir.to_string_lossy().to_string()),
+        readiness: ReadinessSpec {
+            stdout_marker: Some(CORE_READY_MARKER.to_string()),
+            health_url: None,
+            timeout_secs: Some(CORE_READY_TIMEOUT_SECS),
+        },
+        port: PortSpec {
+            strategy: PortStrategy::Pick,
+            value: None,
+            env: Some("API_PORT".to_string()),
+        },
+        restart: RestartSpec::default(),
+        autostart: true,
+        depends_on: Vec::new(),
+        required_by: Vec::new(),
+    }
+}
+
+/// 每次启动前按策略校验 core 目录（见 [crate::integrity]）。
+fn verify_core_integrity(app: &AppHandle, manifest: &ServiceManifest) -> Result<(), String> {
+    match &manifest.cwd {
+        Some(dir) => integrity::check_core_dir(app, Path::new(dir)),
+        None => Ok(()),
+    }
+}
+
+fn on_core_started(app: &AppHandle, pid: u32, port: Option<u16>) {
+    let Some(api_port) = port else {
+        return;
+    };
+    if let Some(state) = app.try_state::<CorePorts>() {
+        *state.api_port.lock().unwrap() = Some(api_port);
+    }
+    log::info!("接口 | http://127.0.0.1:{} | PID: {}", api_port, pid);
+    if let Err(e) = write_runtime(app, pid, api_port) {
+        log::error!("写入运行记录失败: {}", e);
+    }
+}
+
+fn on_core_failed(app: &AppHandle, kind: FailureKind, reason: &str) {
+    // 只有 core 自身的问题（校验未通过、就绪前退出、就绪超时）才回退到内置版本再试一次；
+    // 依赖失败、端口分配失败或侧车缺失换成内置版本也无济于事，不应把更新包记为失败
+    let own_fault = matches!(
+        kind,
+        FailureKind::Rejected | FailureKind::ExitedBeforeReady | FailureKind::ReadyTimeout
+    );
+    if own_fault && updater::fall_back(app, reason) {
+        let app = app.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = restart_core(&app) {
+                log::error!("回退到内置版本后启动失败: {}", e);
+            }
+        });
+        return;
+    }
+    // 多为侧车未找到（请先执行 pnpm run init:runtime）、无法分配端口或依赖未就绪
+    if headless::is_enabled() {
+        log::error!("侧车启动失败: {}", reason);
+        headless::report_failed(app, reason);
+        return;
+    }
+    log::error!("侧车启动失败（应用继续运行）: {}", reason);
+    write_core_env_when_skip(app);
+}
+
+fn on_core_ready(app: &AppHandle, port: Option<u16>) {
+    let _ = app.emit(
+        "core-ready",
+        serde_json::json!({
+            "ready": true,
+            "apiPort": port,
+        }),
+    );
+    log::info!("已 emit core-ready 事件到前端");
+    if headless::is_enabled() {
+        headless::report_ready(app, port);
+    }
+}
+
+/// Setup 与切换 profile 时调用：`TAURI_SKIP_SIDECAR=1` 时只写入 .env，否则注册 core 服务。
+/// 无窗口模式下 core 无法注册即视为启动失败（见 [headless::report_failed]）。
+pub fn start_on_setup(app: &AppHandle) {
+    let skip = std::env::var("TAURI_SKIP_SIDECAR").as_deref() == Ok("1");
+    if skip && headless::is_enabled() {
+        headless::report_failed(app, "无窗口模式下不能设置 TAURI_SKIP_SIDECAR=1");
+    } else if skip {
+        log::warn!("TAURI_SKIP_SIDECAR=1，不启动 Node 服务，已写入 .env 供自启");
+        write_core_env_when_skip(app);
+    } else {
+        log::info!("准备启动 Node 服务...");
+        let result = register_core_on_setup(app);
+        if let Err(e) = &result {
+            log::error!("启动失败: {}", e);
+        }
+        let registered = app
+            .try_state::<Supervisor>()
+            .is_some_and(|s| s.info(CORE_SERVICE_NAME).is_ok());
+        if headless::is_enabled() && !registered {
+            let reason = result.err().unwrap_or_else(|| "未找到 core".to_string());
+            headless::report_failed(app, &reason);
+        }
+    }
+}
+
+/// Setup 阶段调用：若未跳过侧车，则把 resources/core/index.js 注册为托管服务（autostart），
+/// 实际启动由 [crate::services::supervisor::spawn_autostart] 按依赖顺序完成。
+pub fn register_core_on_setup(app: &AppHandle) -> Result<(), String> {
+    // 数据库迁移失败或漂移时拒绝启动，避免 core 在不一致的结构上运行
+    if let Some(e) = db::migrate::blocking_error() {
+        return Err(format!("数据库迁移未完成: {}", e));
+    }
+
     let (_resource_dir, core_dir) = match resolve_core_dir(app) {
         Some(pair) => pair,
         None => {
-            eprintln!("[core] 未找到 core 目录（请先执行 pnpm -C core run build）");
+            log::warn!("未找到 core 目录（请先执行 pnpm -C core run build）");
             write_core_env_when_skip(app);
             return Ok(());
         }
@@ -207,34 +338,15 @@ pub fn start_core_on_setup(app: &AppHandle) -> Result<(), String> {
 
     let index_js = core_dir.join("index.js");
     if !index_js.exists() {
-        eprintln!("[core] index.js 未找到: {}", index_js.display());
+        log::warn!("index.js 未找到: {}", index_js.display());
         write_core_env_when_skip(app);
         return Ok(());
     }
 
-    let api_port = match portpicker::pick_unused_port() {
-        Some(p) => p,
-        None => {
-            eprintln!("[core] 无法分配端口，跳过启动");
-            write_core_env_when_skip(app);
-            return Ok(());
-        }
-    };
-
-    let env_vars = build_core_env(app, api_port);
     if is_dev() {
-        log_dev_paths(&env_vars);
+        log_dev_paths(&core_data_env(app));
     }
 
-    let sidecar = match app.shell().sidecar("toolbox_node") {
-        Ok(cmd) => cmd,
-        Err(e) => {
-            eprintln!("[core] toolbox_node 侧车未找到（请先执行 pnpm run init:runtime）: {}", e);
-            write_core_env_when_skip(app);
-            return Ok(());
-        }
-    };
-
     // macOS: 启动前移除隔离属性，避免 Gatekeeper 延迟
     #[cfg(target_os = "macos")]
     {
@@ -246,66 +358,32 @@ pub fn start_core_on_setup(app: &AppHandle) -> Result<(), String> {
         }
     }
 
-    let env_pairs: Vec<_> = env_vars.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
-    let spawn_start = std::time::Instant::now();
-    eprintln!("[core] 准备 spawn Node 侧车...");
-    match sidecar
-        .arg(&index_js)
-        .current_dir(&core_dir)
-        .envs(env_pairs.iter().map(|(k, v)| (*k, *v)))
-        .spawn()
-    {
-        Ok((mut rx, child)) => {
-            let spawn_elapsed = spawn_start.elapsed().as_millis();
-            let pid = child.pid();
-            eprintln!("[core] toolbox_node 侧车已启动 core | 端口 {} | spawn耗时: {}ms | PID: {}", api_port, spawn_elapsed, pid);
-            if let Some(state) = app.try_state::<CorePorts>() {
-                *state.api_port.lock().unwrap() = Some(api_port);
-            }
-            if let Some(sidecar_state) = app.try_state::<CoreSidecarChild>() {
-                if let Ok(mut g) = sidecar_state.0.lock() {
-                    *g = Some(child);
-                }
-            }
-            SIDECAR_PID.get_or_init(|| Mutex::new(None)).lock().unwrap().replace(pid);
-            println!("[core] 接口 | http://127.0.0.1:{}", api_port);
-            // 在后台消费侧车 stdout/stderr，否则缓冲区满会导致子进程阻塞
-            let app_handle = app.clone();
-            let spawn_start_spawn = spawn_start.clone();
-            let mut first_output = true;
-            tauri::async_runtime::spawn(async move {
-                while let Some(event) = rx.recv().await {
-                    match event {
-                        CommandEvent::Stdout(line) => {
-                            if first_output {
-                                first_output = false;
-                                println!("[core] 收到 Node 进程第一条 stdout，距 spawn 约: {:?}", spawn_start_spawn.elapsed());
-                            }
-                            let _ = std::io::stdout().write_all(&line);
-                            let _ = std::io::stdout().flush();
-                            // 检测 Core 服务就绪标记
-                            if let Ok(text) = String::from_utf8(line.clone()) {
-                                if text.contains("###CORE_READY###") {
-                                    let _ = app_handle.emit("core-ready", serde_json::json!({
-                                        "ready": true,
-                                        "apiPort": api_port,
-                                    }));
-                                    println!("[core] 已 emit core-ready 事件到前端，距 spawn: {:?}", spawn_start_spawn.elapsed());
-                                }
-                            }
-                        }
-                        CommandEvent::Stderr(line) => {
-                            let _ = std::io::stderr().write_all(&line);
-                            let _ = std::io::stderr().flush();
-                        }
-                        _ => {}
-                    }
-                }
-            });
-        }
-        Err(e) => eprintln!("[core] 侧车启动失败（应用继续运行）: {}", e),
-    }
+    let supervisor_state = app
+        .try_state::<Supervisor>()
+        .ok_or_else(|| "Supervisor 未注册".to_string())?;
+    let node = updater::preferred_node(app);
+    supervisor_state.register(
+        core_manifest(&core_dir, &index_js, node.as_deref()),
+        ServiceHooks {
+            on_started: Some(on_core_started),
+            on_ready: Some(on_core_ready),
+            on_failed: Some(on_core_failed),
+            extra_env: Some(core_data_env),
+            before_spawn: Some(verify_core_integrity),
+            log_file: None,
+        },
+    )
+}
 
+/// 停止 core、按当前更新状态重新解析目录并注册，再按依赖顺序启动。阻塞直到启动流程结束。
+/// 启动前即失败（如完整性校验未通过）时同样走 [on_core_failed]，使更新包能回退到内置版本。
+pub fn restart_core(app: &AppHandle) -> Result<(), String> {
+    let _ = supervisor::stop(app, CORE_SERVICE_NAME);
+    register_core_on_setup(app)?;
+    if let Err((kind, e)) = supervisor::try_start(app, CORE_SERVICE_NAME) {
+        on_core_failed(app, kind, &e);
+        return Err(e);
+    }
     Ok(())
 }
 
@@ -313,21 +391,131 @@ fn log_dev_paths(env_vars: &[(String, String)]) {
     const KEYS: &[&str] = &["APP_DATA_DIR", "STORE_PATH", "SQLITE_DB_PATH"];
     for key in KEYS {
         if let Some((_, v)) = env_vars.iter().find(|(k, _)| k == key) {
-            println!("[core] dev: {}={}", key, v);
+            log::debug!("dev: {}={}", key, v);
         }
     }
 }
 
+// ---------------------------------------------------------------------------
+// 运行记录与日志文件（供 CLI 在应用之外查看）
+// ---------------------------------------------------------------------------
+
+/// core 启动后写入 `<profile 数据目录>/core-runtime.json`，应用退出时删除；
+/// 进程异常退出时可能残留，读取方需自行检查 PID 是否存活。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoreRuntime {
+    /// 应用（Tauri）进程
+    pub app_pid: u32,
+    /// core 进程
+    pub pid: u32,
+    pub port: u16,
+    pub profile: String,
+    pub headless: bool,
+    /// Unix 秒
+    pub started_at: u64,
+}
+
+fn runtime_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(profile::data_dir(app)?.join(RUNTIME_FILE_NAME))
+}
+
+fn write_runtime(app: &AppHandle, pid: u32, port: u16) -> Result<(), String> {
+    let runtime = CoreRuntime {
+        app_pid: std::process::id(),
+        pid,
+        port,
+        profile: profile::active(),
+        headless: headless::is_enabled(),
+        started_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+    };
+    let value = serde_json::to_value(&runtime).map_err(|e| e.to_string())?;
+    crate::store::migrate::write_json_atomic(&runtime_path(app)?, &value)
+}
+
+/// 当前 profile 的运行记录；不存在或无法解析时为 None。
+pub fn read_runtime(app: &AppHandle) -> Option<CoreRuntime> {
+    let text = fs::read_to_string(runtime_path(app).ok()?).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// 删除本进程写入的运行记录（应用退出、切换 profile 前调用）。
+pub fn clear_runtime(app: &AppHandle) {
+    if read_runtime(app).is_some_and(|r| r.app_pid == std::process::id()) {
+        if let Ok(path) = runtime_path(app) {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// core 的日志基准文件，解析顺序与 core 的 logger 相同：LOG_PATH、`LOG_DIR/langchain-serve.log`、
+/// `<profile 数据目录>/langchain-serve.log`。
+pub fn log_base_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let env = |key: &str| {
+        std::env::var(key)
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    if let Some(path) = env("LOG_PATH") {
+        return Ok(PathBuf::from(path));
+    }
+    if let Some(dir) = env("LOG_DIR") {
+        return Ok(PathBuf::from(dir).join(CORE_LOG_FILE_NAME));
+    }
+    Ok(profile::data_dir(app)?.join(CORE_LOG_FILE_NAME))
+}
+
+/// core 按日滚动的日志文件（`<基准名>-YYYY-MM-DD<扩展名>`）及其日期，按日期升序。
+pub fn daily_log_files(app: &AppHandle) -> Result<Vec<(String, PathBuf)>, String> {
+    let base = log_base_path(app)?;
+    let dir = base.parent().map(Path::to_path_buf).unwrap_or_default();
+    let stem = base
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = base
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    let prefix = format!("{}-", stem);
+    let mut files: Vec<(String, PathBuf)> = fs::read_dir(&dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|e| {
+                    let name = e.file_name().to_string_lossy().to_string();
+                    let date = name.strip_prefix(&prefix)?.strip_suffix(&ext)?;
+                    let is_date = date.len() == 10
+                        && date.chars().enumerate().all(|(i, c)| {
+                            if i == 4 || i == 7 {
+                                c == '-'
+                            } else {
+                                c.is_ascii_digit()
+                            }
+                        });
+                    is_date.then(|| (date.to_string(), e.path()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    Ok(files)
+}
+
 // ---------------------------------------------------------------------------
 // 跳过侧车时的回退（写入 .env）
 // ---------------------------------------------------------------------------
 
-/// 不启动侧车时：向 core 源码目录写入 .env，内容与 [build_core_env] 一致，供本地自启 core。
+/// 不启动侧车时：向 core 源码目录写入 .env，内容与 [build_core_env] 一致（[SECRET_ENV_KEYS] 除外），供本地自启 core。
 pub fn write_core_env_when_skip(app: &AppHandle) {
     let core_dir = match core_src_dir() {
         Some(d) => d,
         None => {
-            eprintln!("[core] 无法解析 core 目录，跳过写入 .env");
+            log::warn!("无法解析 core 目录，跳过写入 .env");
             return;
         }
     };
@@ -340,14 +528,14 @@ pub fn write_core_env_when_skip(app: &AppHandle) {
     let env_path = core_dir.join(".env");
 
     if let Err(e) = fs::create_dir_all(&core_dir) {
-        eprintln!("[core] 创建 core 目录失败: {}", e);
+        log::error!("创建 core 目录失败: {}", e);
         return;
     }
     match fs::File::create(&env_path).and_then(|mut f| f.write_all(content.as_bytes())) {
-        Ok(()) => println!(
-            "[core] 已写入 {}（TAURI_SKIP_SIDECAR=1 或未启动侧车）",
+        Ok(()) => log::info!(
+            "已写入 {}（TAURI_SKIP_SIDECAR=1 或未启动侧车）",
             env_path.display()
         ),
-        Err(e) => eprintln!("[core] 写入 .env 失败: {}", e),
+        Err(e) => log::error!("写入 .env 失败: {}", e),
     }
 }
@@ -0,0 +1,305 @@
+//! 数据库备份：用 SQLite 在线备份 API 复制到 `<profile 数据目录>/backups/db/`，core 打开着数据库时同样安全。
+//!
+//! - 文件名 `<sqlite_db_name>.<Unix 秒>.<reason>.bak`，即备份 id；reason 见 [BackupReason]；
+//! - 定时备份：后台线程每隔 [CHECK_INTERVAL] 检查一次，距最近一份备份超过
+//!   `db_backup_interval_hours` 即备份；每次执行迁移前另做一份（见 [super::migrate::migrate]）；
+//! - 保留策略：每次备份后，超出 `db_backup_keep` 份或早于 `db_backup_max_age_days` 天的删除，最新一份始终保留；
+//! - 恢复（[db_restore]）：停止 core → 为当前数据库再备份一份 → 临时文件 + rename 替换并清理 `-wal` / `-shm`
+//!   → 重新执行迁移 → 重启 core。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use rusqlite::backup::Backup;
+use rusqlite::Connection;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::{db_path, migrate, open, with_core_stopped, DB_LOCK};
+use crate::config;
+use crate::profile;
+use crate::store::migrate::BACKUP_DIR_NAME;
+use crate::util::now_secs;
+
+const DB_BACKUP_SUBDIR: &str = "db";
+const BACKUP_SUFFIX: &str = ".bak";
+/// 定时备份的检查间隔。
+const CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// 在线备份每步复制的页数与步间暂停：让出锁，避免长时间阻塞 core 的写入。
+const PAGES_PER_STEP: std::os::raw::c_int = 256;
+const STEP_PAUSE: Duration = Duration::from_millis(10);
+
+/// 备份原因，写入文件名。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackupReason {
+    Scheduled,
+    Manual,
+    PreMigration,
+    PreRestore,
+}
+
+impl BackupReason {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Scheduled => "scheduled",
+            Self::Manual => "manual",
+            Self::PreMigration => "pre-migration",
+            Self::PreRestore => "pre-restore",
+        }
+    }
+}
+
+/// 一份备份的描述，`id` 即文件名。
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbBackupInfo {
+    pub id: String,
+    pub db_name: String,
+    pub created_at: u64,
+    pub reason: String,
+    pub size: u64,
+}
+
+/// `<profile 数据目录>/backups/db`
+pub fn backup_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(profile::data_dir(app)?
+        .join(BACKUP_DIR_NAME)
+        .join(DB_BACKUP_SUBDIR))
+}
+
+/// 解析 `<db_name>.<ts>.<reason>.bak`。
+fn parse_backup_name(name: &str) -> Option<(String, u64, String)> {
+    let stem = name.strip_suffix(BACKUP_SUFFIX)?;
+    let (rest, reason) = stem.rsplit_once('.')?;
+    let (db_name, ts) = rest.rsplit_once('.')?;
+    Some((db_name.to_string(), ts.parse().ok()?, reason.to_string()))
+}
+
+/// 列出 `dir` 下属于 `db_name` 的备份，新的在前。
+pub fn list_in(dir: &Path, db_name: &str) -> Vec<DbBackupInfo> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut list: Vec<DbBackupInfo> = entries
+        .flatten()
+        .filter_map(|e| {
+            let id = e.file_name().to_string_lossy().to_string();
+            let (name, created_at, reason) = parse_backup_name(&id)?;
+            (name == db_name).then(|| DbBackupInfo {
+                size: e.metadata().map(|m| m.len()).unwrap_or(0),
+                id,
+                db_name: name,
+                created_at,
+                reason,
+            })
+        })
+        .collect();
+    list.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
+    list
+}
+
+/// 用在线备份 API 把 `conn` 的 main 库复制到 `dest`，先写临时文件再 rename。
+pub fn copy_database(conn: &Connection, dest: &Path) -> Result<(), String> {
+    let file_name = dest
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("无效的目标路径: {}", dest.display()))?;
+    let tmp = dest.with_file_name(format!("{}.tmp", file_name));
+    let result = (|| {
+        let mut out = Connection::open(&tmp).map_err(|e| e.to_string())?;
+        let backup = Backup::new(conn, &mut out).map_err(|e| e.to_string())?;
+        backup
+            .run_to_completion(PAGES_PER_STEP, STEP_PAUSE, None)
+            .map_err(|e| format!("备份失败: {}", e))
+    })();
+    if let Err(e) = result {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+    fs::rename(&tmp, dest).map_err(|e| e.to_string())
+}
+
+/// 备份 `conn` 的 main 库到 `dir`，文件名即备份 id。
+pub fn backup_connection(
+    conn: &Connection,
+    dir: &Path,
+    db_name: &str,
+    reason: BackupReason,
+) -> Result<DbBackupInfo, String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let created_at = now_secs();
+    let id = format!(
+        "{}.{}.{}{}",
+        db_name,
+        created_at,
+        reason.as_str(),
+        BACKUP_SUFFIX
+    );
+    let dest = dir.join(&id);
+    copy_database(conn, &dest)?;
+    Ok(DbBackupInfo {
+        size: fs::metadata(&dest).map(|m| m.len()).unwrap_or(0),
+        id,
+        db_name: db_name.to_string(),
+        created_at,
+        reason: reason.as_str().to_string(),
+    })
+}
+
+/// 按份数与天数清理旧备份，最新一份始终保留；返回删除的 id。
+pub fn prune_in(dir: &Path, db_name: &str, keep: usize, max_age_days: u64) -> Vec<String> {
+    let cutoff = now_secs().saturating_sub(max_age_days * 24 * 60 * 60);
+    list_in(dir, db_name)
+        .into_iter()
+        .enumerate()
+        .filter(|(i, b)| *i > 0 && (*i >= keep || b.created_at < cutoff))
+        .filter_map(|(_, b)| fs::remove_file(dir.join(&b.id)).ok().map(|_| b.id))
+        .collect()
+}
+
+/// 备份配置中的数据库并按配置清理旧备份。数据库文件不存在时返回 Ok(None)。
+pub fn backup_now(app: &AppHandle, reason: BackupReason) -> Result<Option<DbBackupInfo>, String> {
+    let path = db_path(app)?;
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let conn = open(&path)?;
+    backup_with(app, &conn, reason).map(Some)
+}
+
+/// 用已打开的连接备份（迁移前调用），并按配置清理旧备份。
+pub fn backup_with(
+    app: &AppHandle,
+    conn: &Connection,
+    reason: BackupReason,
+) -> Result<DbBackupInfo, String> {
+    let dir = backup_dir(app)?;
+    let db_name = config::get_sqlite_db_name(app);
+    let info = backup_connection(conn, &dir, &db_name, reason)?;
+    log::info!("已备份 {}（{} 字节）", info.id, info.size);
+    let removed = prune_in(
+        &dir,
+        &db_name,
+        config::get_db_backup_keep(app),
+        config::get_db_backup_max_age_days(app),
+    );
+    if !removed.is_empty() {
+        log::info!("已清理 {} 份旧备份", removed.len());
+    }
+    Ok(info)
+}
+
+/// Setup 阶段调用：后台线程定期检查，距最近一份备份超过配置间隔时做一次定时备份。
+pub fn spawn_scheduler(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        let interval = config::get_db_backup_interval_hours(&app) * 60 * 60;
+        let latest = backup_dir(&app)
+            .map(|dir| list_in(&dir, &config::get_sqlite_db_name(&app)))
+            .ok()
+            .and_then(|list| list.first().map(|b| b.created_at));
+        let due = latest.is_none_or(|at| now_secs().saturating_sub(at) >= interval);
+        if due {
+            let _guard = DB_LOCK.lock();
+            if let Err(e) = backup_now(&app, BackupReason::Scheduled) {
+                log::error!("定时备份失败: {}", e);
+            }
+        }
+        std::thread::sleep(CHECK_INTERVAL);
+    });
+}
+
+fn target_file_name(target: &Path) -> Result<String, String> {
+    target
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("无效的数据库路径: {}", target.display()))
+}
+
+/// 把 `backup` 复制到 `target` 同目录的临时文件，返回临时文件路径。
+/// 须在做恢复前备份之前调用：之后的备份与清理不会再影响要恢复的内容。
+fn stage_restore(backup: &Path, target: &Path) -> Result<PathBuf, String> {
+    let tmp = target.with_file_name(format!("{}.restoring", target_file_name(target)?));
+    fs::copy(backup, &tmp).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        format!("复制备份失败: {}", e)
+    })?;
+    Ok(tmp)
+}
+
+/// 用 [stage_restore] 得到的临时文件替换 `target`（rename），并删除旧的 `-wal` / `-shm`。
+/// 调用方须保证没有其他连接打开 `target`。
+fn swap_in(tmp: &Path, target: &Path) -> Result<(), String> {
+    let file_name = target_file_name(target)?;
+    for suffix in ["-wal", "-shm"] {
+        let side = target.with_file_name(format!("{}{}", file_name, suffix));
+        if side.exists() {
+            fs::remove_file(&side).map_err(|e| format!("删除 {} 失败: {}", side.display(), e))?;
+        }
+    }
+    fs::rename(tmp, target).map_err(|e| format!("替换数据库失败: {}", e))
+}
+
+/// 恢复前备份当前数据库；不做清理，连接在返回前关闭。数据库文件不存在时跳过。
+fn pre_restore_backup(target: &Path, dir: &Path, db_name: &str) -> Result<(), String> {
+    if !target.is_file() {
+        return Ok(());
+    }
+    let conn = open(target)?;
+    let info = backup_connection(&conn, dir, db_name, BackupReason::PreRestore)?;
+    log::info!("已备份 {}（{} 字节）", info.id, info.size);
+    Ok(())
+}
+
+/// 列出当前数据库的全部备份（新的在前）。
+#[tauri::command]
+pub fn db_list_backups(app: AppHandle) -> Result<Vec<DbBackupInfo>, String> {
+    Ok(list_in(
+        &backup_dir(&app)?,
+        &config::get_sqlite_db_name(&app),
+    ))
+}
+
+/// 立即备份一次。
+#[tauri::command]
+pub async fn db_backup(app: AppHandle) -> Result<Option<DbBackupInfo>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let _guard = DB_LOCK.lock().map_err(|e| e.to_string())?;
+        backup_now(&app, BackupReason::Manual)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// 用指定备份恢复数据库：停止 core、先复制选中的备份、再备份当前数据库（不清理旧备份，
+/// 以免选中的备份被挤出保留份数）、原子替换、重新迁移，再按原状态重启 core。
+#[tauri::command]
+pub async fn db_restore(app: AppHandle, id: String) -> Result<DbBackupInfo, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let db_name = config::get_sqlite_db_name(&app);
+        let dir = backup_dir(&app)?;
+        let info = list_in(&dir, &db_name)
+            .into_iter()
+            .find(|b| b.id == id)
+            .ok_or_else(|| format!("备份不存在: {}", id))?;
+        let _guard = DB_LOCK.lock().map_err(|e| e.to_string())?;
+        with_core_stopped(&app, || {
+            let target = db_path(&app)?;
+            let staged = stage_restore(&dir.join(&info.id), &target)?;
+            let swapped =
+                pre_restore_backup(&target, &dir, &db_name).and_then(|_| swap_in(&staged, &target));
+            if let Err(e) = swapped {
+                let _ = fs::remove_file(&staged);
+                return Err(e);
+            }
+            log::info!("已从 {} 恢复数据库", info.id);
+            // 备份可能早于当前结构，补齐迁移；结果决定 core 能否重启
+            migrate::run_on_setup(&app);
+            Ok(())
+        })?;
+        Ok(info)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
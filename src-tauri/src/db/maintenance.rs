@@ -0,0 +1,351 @@
+//! 数据库维护：完整性检查、VACUUM、ANALYZE 与统计。
+//!
+//! 与运行中的 core 协调：
+//! - `integrity_check`、统计与 `ANALYZE` 都是普通的读 / 短事务，在 WAL 下可与 core 并发，遇锁等待 [BUSY_TIMEOUT]；
+//! - `VACUUM` 需要独占数据库，执行期间暂停 core（见 [super::with_core_stopped]），完成后重启。
+//!
+//! 耗时操作通过 `db-maintenance-progress` 事件报告进度，负载见 [MaintenanceProgress]：
+//! 按表执行的操作带 done / total，单条长语句每隔 [HEARTBEAT_INTERVAL] 发一次心跳。
+
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use rusqlite::Connection;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use super::{blocking, db_path, open, with_core_stopped};
+
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+const HEARTBEAT_INTERVAL: Duration = Duration::from_millis(500);
+/// 进度回调的 VM 指令间隔。
+const PROGRESS_OPS: i32 = 10_000;
+/// integrity_check 最多返回的错误条数。
+const MAX_INTEGRITY_ERRORS: usize = 100;
+
+/// `db-maintenance-progress` 事件负载。
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceProgress {
+    /// "integrity_check" | "vacuum" | "analyze" | "stats"
+    pub operation: &'static str,
+    /// "started" | "running" | "finished" | "failed"
+    pub phase: &'static str,
+    /// 当前处理的表
+    pub table: Option<String>,
+    pub done: Option<usize>,
+    pub total: Option<usize>,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityResult {
+    pub ok: bool,
+    /// `PRAGMA integrity_check` 的错误行，最多 [MAX_INTEGRITY_ERRORS] 条
+    pub errors: Vec<String>,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VacuumResult {
+    pub size_before: u64,
+    pub size_after: u64,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyzeResult {
+    pub tables: usize,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableStats {
+    pub name: String,
+    pub rows: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbStats {
+    pub path: String,
+    /// 主文件字节数
+    pub file_size: u64,
+    /// `-wal` 文件字节数（不存在为 0）
+    pub wal_size: u64,
+    pub page_size: i64,
+    pub page_count: i64,
+    pub freelist_count: i64,
+    pub journal_mode: String,
+    pub tables: Vec<TableStats>,
+}
+
+/// 进度上报：`emit` 为 None 时（如 CLI）只计时不发事件。
+pub struct Reporter {
+    operation: &'static str,
+    started: Instant,
+    emit: Option<AppHandle>,
+}
+
+fn emit_progress(
+    app: &AppHandle,
+    operation: &'static str,
+    started: Instant,
+    phase: &'static str,
+    table: Option<&str>,
+    done: Option<usize>,
+    total: Option<usize>,
+) {
+    let _ = app.emit(
+        "db-maintenance-progress",
+        MaintenanceProgress {
+            operation,
+            phase,
+            table: table.map(String::from),
+            done,
+            total,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        },
+    );
+}
+
+impl Reporter {
+    pub fn new(operation: &'static str, emit: Option<&AppHandle>) -> Self {
+        let r = Self {
+            operation,
+            started: Instant::now(),
+            emit: emit.cloned(),
+        };
+        r.send("started", None, None, None);
+        r
+    }
+
+    fn elapsed_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+
+    fn send(
+        &self,
+        phase: &'static str,
+        table: Option<&str>,
+        done: Option<usize>,
+        total: Option<usize>,
+    ) {
+        if let Some(app) = &self.emit {
+            emit_progress(app, self.operation, self.started, phase, table, done, total);
+        }
+    }
+
+    fn step(&self, table: &str, done: usize, total: usize) {
+        self.send("running", Some(table), Some(done), Some(total));
+    }
+
+    /// 以 `result` 是否成功发送 finished / failed。
+    fn finish<T>(&self, result: Result<T, String>) -> Result<T, String> {
+        let phase = if result.is_ok() { "finished" } else { "failed" };
+        self.send(phase, None, None, None);
+        result
+    }
+
+    /// 在 `conn` 上执行 `f`，期间按 [HEARTBEAT_INTERVAL] 发送心跳。
+    fn with_heartbeat<T>(&self, conn: &Connection, f: impl FnOnce() -> T) -> T {
+        let Some(app) = self.emit.clone() else {
+            return f();
+        };
+        let (operation, started) = (self.operation, self.started);
+        let mut last = Instant::now();
+        conn.progress_handler(
+            PROGRESS_OPS,
+            Some(move || {
+                if last.elapsed() >= HEARTBEAT_INTERVAL {
+                    last = Instant::now();
+                    emit_progress(&app, operation, started, "running", None, None, None);
+                }
+                false
+            }),
+        );
+        let out = f();
+        conn.progress_handler(PROGRESS_OPS, None::<fn() -> bool>);
+        out
+    }
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn file_len(path: &Path) -> u64 {
+    fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+fn wal_path(path: &Path) -> std::path::PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!("{}-wal", name))
+}
+
+/// 用户表（不含 `sqlite_` 内部表），按名称排序。
+pub fn user_tables(conn: &Connection) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |r| r.get::<_, String>(0))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+pub fn integrity_check(conn: &Connection, reporter: &Reporter) -> Result<IntegrityResult, String> {
+    let result = reporter.with_heartbeat(conn, || -> Result<Vec<String>, String> {
+        let sql = format!("PRAGMA integrity_check({})", MAX_INTEGRITY_ERRORS);
+        let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |r| r.get::<_, String>(0))
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())
+    });
+    let result = result.map(|lines| {
+        let ok = lines.len() == 1 && lines[0] == "ok";
+        IntegrityResult {
+            ok,
+            errors: if ok { Vec::new() } else { lines },
+            elapsed_ms: reporter.elapsed_ms(),
+        }
+    });
+    reporter.finish(result)
+}
+
+pub fn analyze(conn: &Connection, reporter: &Reporter) -> Result<AnalyzeResult, String> {
+    let result = (|| {
+        let tables = user_tables(conn)?;
+        for (i, table) in tables.iter().enumerate() {
+            reporter.step(table, i, tables.len());
+            conn.execute_batch(&format!("ANALYZE {}", quote_ident(table)))
+                .map_err(|e| format!("ANALYZE {} 失败: {}", table, e))?;
+        }
+        Ok(AnalyzeResult {
+            tables: tables.len(),
+            elapsed_ms: reporter.elapsed_ms(),
+        })
+    })();
+    reporter.finish(result)
+}
+
+pub fn stats(conn: &Connection, path: &Path, reporter: &Reporter) -> Result<DbStats, String> {
+    let result = (|| {
+        let pragma = |name: &str| -> Result<i64, String> {
+            conn.query_row(&format!("PRAGMA {}", name), [], |r| r.get(0))
+                .map_err(|e| e.to_string())
+        };
+        let journal_mode: String = conn
+            .query_row("PRAGMA journal_mode", [], |r| r.get(0))
+            .map_err(|e| e.to_string())?;
+        let names = user_tables(conn)?;
+        let mut tables = Vec::with_capacity(names.len());
+        for (i, name) in names.iter().enumerate() {
+            reporter.step(name, i, names.len());
+            let rows = reporter.with_heartbeat(conn, || {
+                conn.query_row(
+                    &format!("SELECT COUNT(*) FROM {}", quote_ident(name)),
+                    [],
+                    |r| r.get(0),
+                )
+            });
+            tables.push(TableStats {
+                name: name.clone(),
+                rows: rows.map_err(|e| format!("统计 {} 失败: {}", name, e))?,
+            });
+        }
+        Ok(DbStats {
+            path: path.to_string_lossy().to_string(),
+            file_size: file_len(path),
+            wal_size: file_len(&wal_path(path)),
+            page_size: pragma("page_size")?,
+            page_count: pragma("page_count")?,
+            freelist_count: pragma("freelist_count")?,
+            journal_mode,
+            tables,
+        })
+    })();
+    reporter.finish(result)
+}
+
+/// VACUUM：调用方须保证没有其他连接在写（见 [db_vacuum]）。之后做一次 WAL checkpoint 让文件大小反映结果。
+pub fn vacuum(conn: &Connection, path: &Path, reporter: &Reporter) -> Result<VacuumResult, String> {
+    let size_before = file_len(path) + file_len(&wal_path(path));
+    let result = reporter
+        .with_heartbeat(conn, || {
+            conn.execute_batch("VACUUM; PRAGMA wal_checkpoint(TRUNCATE);")
+        })
+        .map_err(|e| format!("VACUUM 失败: {}", e))
+        .map(|_| VacuumResult {
+            size_before,
+            size_after: file_len(path) + file_len(&wal_path(path)),
+            elapsed_ms: reporter.elapsed_ms(),
+        });
+    reporter.finish(result)
+}
+
+/// 打开配置中的数据库（不存在时报错而不是新建空库）。
+pub(crate) fn open_existing(app: &AppHandle) -> Result<(Connection, std::path::PathBuf), String> {
+    let path = db_path(app)?;
+    if !path.is_file() {
+        return Err(format!("数据库不存在: {}", path.display()));
+    }
+    let conn = open(&path)?;
+    conn.busy_timeout(BUSY_TIMEOUT).map_err(|e| e.to_string())?;
+    Ok((conn, path))
+}
+
+/// `PRAGMA integrity_check`，与 core 并发执行。
+#[tauri::command]
+pub async fn db_integrity_check(app: AppHandle) -> Result<IntegrityResult, String> {
+    blocking(app, |app| {
+        let (conn, _) = open_existing(app)?;
+        integrity_check(&conn, &Reporter::new("integrity_check", Some(app)))
+    })
+    .await
+}
+
+/// 逐表 `ANALYZE`，与 core 并发执行。
+#[tauri::command]
+pub async fn db_analyze(app: AppHandle) -> Result<AnalyzeResult, String> {
+    blocking(app, |app| {
+        let (conn, _) = open_existing(app)?;
+        analyze(&conn, &Reporter::new("analyze", Some(app)))
+    })
+    .await
+}
+
+/// 文件大小、页数、空闲页与各表行数，与 core 并发执行。
+#[tauri::command]
+pub async fn db_stats(app: AppHandle) -> Result<DbStats, String> {
+    blocking(app, |app| {
+        let (conn, path) = open_existing(app)?;
+        stats(&conn, &path, &Reporter::new("stats", Some(app)))
+    })
+    .await
+}
+
+/// `VACUUM`：需要独占数据库，执行期间暂停 core。
+#[tauri::command]
+pub async fn db_vacuum(app: AppHandle) -> Result<VacuumResult, String> {
+    blocking(app, |app| {
+        with_core_stopped(app, || {
+            let (conn, path) = open_existing(app)?;
+            vacuum(&conn, &path, &Reporter::new("vacuum", Some(app)))
+        })
+    })
+    .await
+}
@@ -0,0 +1,232 @@
+//! SQLite 结构迁移：按版本号顺序执行 `migrations/*.sql`（随应用打包为资源），在 core 启动前完成。
+//!
+//! - 文件名为 `<version>_<name>.sql`，如 `0001_create_test.sql`；version 为正整数且不可重复；
+//! - 已执行的迁移记录在 `_migrations` 表（version、name、SHA-256、执行时间），每个迁移在独立事务中执行；
+//! - 已执行迁移的文件被修改或删除、或数据库版本高于应用自带的迁移（降级安装）视为漂移：
+//!   不再执行任何迁移并拒绝启动 core，需人工处理，见 [blocking_error]。
+//!
+//! 有待执行的迁移且数据库已有内容时，执行前先备份一份（见 [super::backup]）。
+//!
+//! 新增表或改表：在 `migrations/` 追加一个更大版本号的文件，不要修改已发布的文件。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::backup::{self, BackupReason};
+use super::db_path;
+use crate::util::{now_secs, sha256_hex};
+
+const MIGRATIONS_RESOURCE_DIR: &str = "migrations";
+const MIGRATIONS_TABLE_SQL: &str = "CREATE TABLE IF NOT EXISTS _migrations (
+  version INTEGER PRIMARY KEY,
+  name TEXT NOT NULL,
+  checksum TEXT NOT NULL,
+  applied_at INTEGER NOT NULL
+)";
+
+/// 最近一次 setup 迁移的失败原因；存在时 core 不启动。
+static BLOCKING_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
+/// 一个迁移文件。
+#[derive(Debug, Clone)]
+pub struct MigrationFile {
+    pub version: i64,
+    pub name: String,
+    pub checksum: String,
+    pub sql: String,
+}
+
+/// 迁移状态（供命令与 CLI 展示）。
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: String,
+    /// 执行时间（Unix 秒）；未执行为 None
+    pub applied_at: Option<i64>,
+    /// 文件与记录不一致、或文件已不存在
+    pub drifted: bool,
+}
+
+/// `<resource_dir>/migrations`
+pub fn migrations_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .resolve(MIGRATIONS_RESOURCE_DIR, BaseDirectory::Resource)
+        .map_err(|e| e.to_string())
+}
+
+/// 读取并按 version 排序目录下全部迁移文件；目录不存在时返回空表。
+pub fn load_files(dir: &Path) -> Result<Vec<MigrationFile>, String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(Vec::new());
+    };
+    let mut files = Vec::new();
+    for path in entries.flatten().map(|e| e.path()) {
+        let Some(file_name) = path.file_name().map(|n| n.to_string_lossy().to_string()) else {
+            continue;
+        };
+        let Some(stem) = file_name.strip_suffix(".sql") else {
+            continue;
+        };
+        let (version, name) = stem
+            .split_once('_')
+            .ok_or_else(|| format!("迁移文件名应为 <version>_<name>.sql: {}", file_name))?;
+        let version: i64 = version
+            .parse()
+            .ok()
+            .filter(|v| *v > 0)
+            .ok_or_else(|| format!("迁移文件版本号无效: {}", file_name))?;
+        let bytes = fs::read(&path).map_err(|e| format!("读取 {} 失败: {}", file_name, e))?;
+        let sql = String::from_utf8(bytes.clone())
+            .map_err(|_| format!("迁移文件不是 UTF-8: {}", file_name))?;
+        files.push(MigrationFile {
+            version,
+            name: name.to_string(),
+            checksum: sha256_hex(&bytes),
+            sql,
+        });
+    }
+    files.sort_by_key(|f| f.version);
+    if let Some(w) = files.windows(2).find(|w| w[0].version == w[1].version) {
+        return Err(format!("迁移版本号重复: {}", w[0].version));
+    }
+    Ok(files)
+}
+
+/// `_migrations` 中的记录：(version, name, checksum, applied_at)。
+fn applied(conn: &Connection) -> Result<Vec<(i64, String, String, i64)>, String> {
+    conn.execute_batch(MIGRATIONS_TABLE_SQL)
+        .map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare("SELECT version, name, checksum, applied_at FROM _migrations ORDER BY version")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// 合并文件与记录得到状态表（按 version 排序）。
+pub fn status(conn: &Connection, files: &[MigrationFile]) -> Result<Vec<MigrationStatus>, String> {
+    let records = applied(conn)?;
+    let mut out: Vec<MigrationStatus> = files
+        .iter()
+        .map(|f| {
+            let record = records.iter().find(|r| r.0 == f.version);
+            MigrationStatus {
+                version: f.version,
+                name: f.name.clone(),
+                applied_at: record.map(|r| r.3),
+                drifted: record.is_some_and(|r| r.2 != f.checksum),
+            }
+        })
+        .collect();
+    for r in records
+        .iter()
+        .filter(|r| !files.iter().any(|f| f.version == r.0))
+    {
+        out.push(MigrationStatus {
+            version: r.0,
+            name: r.1.clone(),
+            applied_at: Some(r.3),
+            drifted: true,
+        });
+    }
+    out.sort_by_key(|s| s.version);
+    Ok(out)
+}
+
+/// 检查漂移：已执行迁移的文件须存在且校验和一致。
+fn check_drift(conn: &Connection, files: &[MigrationFile]) -> Result<(), String> {
+    for (version, name, checksum, _) in applied(conn)? {
+        match files.iter().find(|f| f.version == version) {
+            None => {
+                return Err(format!(
+                    "数据库已执行迁移 {:04}_{}，但应用中没有该文件（可能安装了较旧的版本）",
+                    version, name
+                ))
+            }
+            Some(f) if f.checksum != checksum => {
+                return Err(format!(
+                    "迁移 {:04}_{} 在执行后被修改（校验和不一致）",
+                    version, name
+                ))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// 未执行的迁移（按 version 升序）；存在漂移时返回错误。
+pub fn pending(conn: &Connection, files: &[MigrationFile]) -> Result<Vec<MigrationFile>, String> {
+    check_drift(conn, files)?;
+    let done: Vec<i64> = applied(conn)?.into_iter().map(|r| r.0).collect();
+    Ok(files
+        .iter()
+        .filter(|f| !done.contains(&f.version))
+        .cloned()
+        .collect())
+}
+
+/// 依次执行未执行的迁移，每个迁移与其记录在同一事务中提交；返回本次执行的版本。
+pub fn apply_pending(conn: &mut Connection, files: &[MigrationFile]) -> Result<Vec<i64>, String> {
+    let todo = pending(conn, files)?;
+    let mut done = Vec::new();
+    for m in todo {
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        tx.execute_batch(&m.sql)
+            .map_err(|e| format!("执行迁移 {:04}_{} 失败: {}", m.version, m.name, e))?;
+        tx.execute(
+            "INSERT INTO _migrations (version, name, checksum, applied_at) VALUES (?1, ?2, ?3, ?4)",
+            params![m.version, m.name, m.checksum, now_secs() as i64],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())?;
+        log::info!("已执行迁移 {:04}_{}", m.version, m.name);
+        done.push(m.version);
+    }
+    Ok(done)
+}
+
+/// 打开 `<app_data>/<sqlite_db_name>` 并执行迁移；供 setup 与 CLI 使用。
+/// 已有数据的库在执行前先备份，备份失败则不迁移。
+pub fn migrate(app: &AppHandle) -> Result<Vec<i64>, String> {
+    let files = load_files(&migrations_dir(app)?)?;
+    let path = db_path(app)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let existed = fs::metadata(&path).is_ok_and(|m| m.len() > 0);
+    let mut conn = super::open(&path)?;
+    if existed && !pending(&conn, &files)?.is_empty() {
+        backup::backup_with(app, &conn, BackupReason::PreMigration)
+            .map_err(|e| format!("迁移前备份失败: {}", e))?;
+    }
+    apply_pending(&mut conn, &files)
+}
+
+/// 最近一次 setup 迁移失败的原因；core 据此拒绝启动。
+pub fn blocking_error() -> Option<String> {
+    BLOCKING_ERROR.lock().ok().and_then(|e| e.clone())
+}
+
+/// Setup 阶段、core 启动之前调用。失败时记录原因并 emit `db-migration-failed`。
+pub fn run_on_setup(app: &AppHandle) {
+    let result = migrate(app);
+    let error = result.as_ref().err().cloned();
+    if let Some(e) = &error {
+        log::error!("迁移失败，core 将不会启动: {}", e);
+        let _ = app.emit("db-migration-failed", serde_json::json!({ "reason": e }));
+    }
+    if let Ok(mut slot) = BLOCKING_ERROR.lock() {
+        *slot = error;
+    }
+}
@@ -0,0 +1,87 @@
+//! core 使用的 SQLite 数据库 `<profile 数据目录>/<sqlite_db_name>`（即 core 环境变量 SQLITE_DB_PATH，见 [crate::profile]）。
+//!
+//! - 结构迁移见 [migrate]，在 setup 阶段、core 启动前执行；
+//! - 定时备份与恢复见 [backup]；
+//! - 完整性检查、VACUUM、ANALYZE 与统计见 [maintenance]；
+//! - 只读 SQL 控制台见 [query]。
+//!
+//! 备份、恢复等整库操作经 [DB_LOCK] 串行执行；需要独占数据库时用 [with_core_stopped] 暂停 core。
+
+pub mod backup;
+pub mod maintenance;
+pub mod migrate;
+pub mod query;
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use rusqlite::Connection;
+use tauri::{AppHandle, Manager};
+
+use crate::config;
+use crate::core::{restart_core, CORE_SERVICE_NAME};
+use crate::profile;
+use crate::services::supervisor::{self, Supervisor};
+
+/// 整库操作（备份、恢复、维护）互斥锁。
+pub(crate) static DB_LOCK: Mutex<()> = Mutex::new(());
+
+/// `<profile 数据目录>/<sqlite_db_name>`，与 core 的 SQLITE_DB_PATH 一致。
+pub fn db_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(profile::data_dir(app)?.join(config::get_sqlite_db_name(app)))
+}
+
+/// 打开数据库文件（不存在时新建）。
+pub fn open(path: &Path) -> Result<Connection, String> {
+    Connection::open(path).map_err(|e| format!("打开数据库 {} 失败: {}", path.display(), e))
+}
+
+/// 暂停 core 执行 `f`：core 在运行则先停止，`f` 结束后（无论成败）重新启动；未运行时直接执行。
+pub(crate) fn with_core_stopped<T>(
+    app: &AppHandle,
+    f: impl FnOnce() -> Result<T, String>,
+) -> Result<T, String> {
+    let running = app
+        .try_state::<Supervisor>()
+        .and_then(|s| s.info(CORE_SERVICE_NAME).ok())
+        .is_some_and(|info| info.pid.is_some());
+    if running {
+        supervisor::stop(app, CORE_SERVICE_NAME)?;
+        log::info!("已暂停 core");
+    }
+    let result = f();
+    if running {
+        if let Err(e) = restart_core(app) {
+            log::error!("重启 core 失败: {}", e);
+        }
+    }
+    result
+}
+
+/// 在阻塞线程中持 [DB_LOCK] 执行。
+pub(crate) async fn blocking<T: Send + 'static>(
+    app: AppHandle,
+    f: impl FnOnce(&AppHandle) -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let _guard = DB_LOCK.lock().map_err(|e| e.to_string())?;
+        f(&app)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// 当前 profile 的数据库文件绝对路径；前端以 `sqlite:<path>` 交给 tauri-plugin-sql，
+/// 与 core、迁移使用同一文件（插件对相对路径按 app_config_dir 解析）。
+#[tauri::command]
+pub fn db_location(app: AppHandle) -> Result<String, String> {
+    Ok(db_path(&app)?.to_string_lossy().to_string())
+}
+
+/// 迁移状态：应用自带的迁移文件与 `_migrations` 记录合并。
+#[tauri::command]
+pub fn db_migrations(app: AppHandle) -> Result<Vec<migrate::MigrationStatus>, String> {
+    let files = migrate::load_files(&migrate::migrations_dir(&app)?)?;
+    let conn = open(&db_path(&app)?)?;
+    migrate::status(&conn, &files)
+}
@@ -0,0 +1,397 @@
+//! 只读 SQL 控制台：在独立的只读连接上执行一条查询，供调试时查看 core 数据库，
+//! 无需给 webview 开放 `sql:allow-execute`。
+//!
+//! 三层限制写入：以 `SQLITE_OPEN_READ_ONLY` 打开、`PRAGMA query_only = ON`、
+//! 以及 authorizer 只放行读表、SELECT、函数与只读 PRAGMA（拒绝 ATTACH、写 PRAGMA 等）。
+//!
+//! 每次只执行一条语句，结果最多 [MAX_ROWS] 行（可用 `maxRows` 调小），执行超过 [QUERY_TIMEOUT] 即中断。
+
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use rusqlite::hooks::{AuthAction, AuthContext, Authorization};
+use rusqlite::types::{Value, ValueRef};
+use rusqlite::{params_from_iter, Batch, Connection, OpenFlags};
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::{blocking, db_path};
+
+const BUSY_TIMEOUT: Duration = Duration::from_secs(2);
+const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_MAX_ROWS: usize = 500;
+const MAX_ROWS: usize = 5_000;
+/// 可不带参数查询的只读 PRAGMA。不在表中的一律拒绝：
+/// 不带参数的 PRAGMA 也可能有副作用（如 `optimize`、`wal_checkpoint`、`shrink_memory`）。
+const READ_PRAGMAS: &[&str] = &[
+    "application_id",
+    "auto_vacuum",
+    "collation_list",
+    "compile_options",
+    "data_version",
+    "database_list",
+    "encoding",
+    "foreign_key_check",
+    "foreign_keys",
+    "freelist_count",
+    "function_list",
+    "integrity_check",
+    "journal_mode",
+    "module_list",
+    "page_count",
+    "page_size",
+    "pragma_list",
+    "query_only",
+    "quick_check",
+    "schema_version",
+    "table_list",
+    "user_version",
+];
+/// 可带参数调用的只读 PRAGMA（参数为表名、索引名或检查行数）；其余 PRAGMA 带参数即赋值。
+const READ_PRAGMAS_WITH_ARG: &[&str] = &[
+    "table_info",
+    "table_xinfo",
+    "index_list",
+    "index_info",
+    "index_xinfo",
+    "foreign_key_list",
+    "foreign_key_check",
+    "integrity_check",
+    "quick_check",
+    "table_list",
+];
+/// 进度回调的 VM 指令间隔，用于检查超时。
+const PROGRESS_OPS: i32 = 1_000;
+/// JS number 能精确表示的最大整数，超出的整数以字符串返回。
+const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+/// 一列的描述。
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryColumn {
+    pub name: String,
+    /// 建表时声明的类型；表达式列为 None
+    pub decl_type: Option<String>,
+    /// 结果中非 NULL 值的存储类型："integer" | "real" | "text" | "blob"，
+    /// 类型不一致为 "mixed"，全为 NULL 或无结果为 None
+    pub value_type: Option<&'static str>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryResult {
+    pub columns: Vec<QueryColumn>,
+    /// 按列顺序的值：NULL → null，整数 / 浮点 → number（超出安全整数范围的整数为字符串），
+    /// 文本 → string，BLOB → base64 string
+    pub rows: Vec<Vec<serde_json::Value>>,
+    /// 结果超过行数上限被截断
+    pub truncated: bool,
+    pub elapsed_ms: u64,
+}
+
+/// PRAGMA 是否在只读白名单中：不带参数查 [READ_PRAGMAS]，带参数查 [READ_PRAGMAS_WITH_ARG]。
+fn is_read_pragma(name: &str, has_arg: bool) -> bool {
+    let allowed = if has_arg {
+        READ_PRAGMAS_WITH_ARG
+    } else {
+        READ_PRAGMAS
+    };
+    allowed.iter().any(|p| p.eq_ignore_ascii_case(name))
+}
+
+/// 只放行读操作。
+fn authorize(ctx: AuthContext<'_>) -> Authorization {
+    match ctx.action {
+        AuthAction::Select
+        | AuthAction::Read { .. }
+        | AuthAction::Function { .. }
+        | AuthAction::Recursive => Authorization::Allow,
+        AuthAction::Pragma {
+            pragma_name,
+            pragma_value,
+        } if is_read_pragma(pragma_name, pragma_value.is_some()) => Authorization::Allow,
+        _ => Authorization::Deny,
+    }
+}
+
+/// 以只读方式打开 `path`，并设置 `query_only` 与 authorizer。
+pub fn open_read_only(path: &std::path::Path) -> Result<Connection, String> {
+    if !path.is_file() {
+        return Err(format!("数据库不存在: {}", path.display()));
+    }
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(|e| format!("打开数据库 {} 失败: {}", path.display(), e))?;
+    conn.busy_timeout(BUSY_TIMEOUT).map_err(|e| e.to_string())?;
+    conn.execute_batch("PRAGMA query_only = ON")
+        .map_err(|e| e.to_string())?;
+    conn.authorizer(Some(authorize));
+    Ok(conn)
+}
+
+/// JSON 参数转为 SQLite 值：布尔按 0 / 1，数组与对象不支持。
+fn to_sql_value(index: usize, v: &serde_json::Value) -> Result<Value, String> {
+    match v {
+        serde_json::Value::Null => Ok(Value::Null),
+        serde_json::Value::Bool(b) => Ok(Value::Integer(*b as i64)),
+        serde_json::Value::Number(n) => n
+            .as_i64()
+            .map(Value::Integer)
+            .or_else(|| n.as_f64().map(Value::Real))
+            .ok_or_else(|| format!("参数 {} 超出范围: {}", index + 1, n)),
+        serde_json::Value::String(s) => Ok(Value::Text(s.clone())),
+        _ => Err(format!(
+            "参数 {} 只能是 null、布尔、数字或字符串",
+            index + 1
+        )),
+    }
+}
+
+/// 被 authorizer 拒绝时给出可读的原因。
+fn describe(e: rusqlite::Error) -> String {
+    match e.sqlite_error_code() {
+        Some(rusqlite::ErrorCode::AuthorizationForStatementDenied) => {
+            format!("只允许只读查询（{}）", e)
+        }
+        _ => e.to_string(),
+    }
+}
+
+fn to_json(v: ValueRef<'_>) -> (Option<&'static str>, serde_json::Value) {
+    match v {
+        ValueRef::Null => (None, serde_json::Value::Null),
+        ValueRef::Integer(i) if i.abs() > MAX_SAFE_INTEGER => {
+            (Some("integer"), serde_json::Value::String(i.to_string()))
+        }
+        ValueRef::Integer(i) => (Some("integer"), i.into()),
+        ValueRef::Real(f) => (
+            Some("real"),
+            serde_json::Number::from_f64(f)
+                .map(serde_json::Value::Number)
+                .unwrap_or(serde_json::Value::Null),
+        ),
+        ValueRef::Text(t) => (Some("text"), String::from_utf8_lossy(t).into_owned().into()),
+        ValueRef::Blob(b) => (
+            Some("blob"),
+            base64::engine::general_purpose::STANDARD.encode(b).into(),
+        ),
+    }
+}
+
+/// 在 `conn` 上执行单条只读语句。
+pub fn run(
+    conn: &Connection,
+    sql: &str,
+    params: &[serde_json::Value],
+    max_rows: usize,
+) -> Result<QueryResult, String> {
+    let started = Instant::now();
+    let values = params
+        .iter()
+        .enumerate()
+        .map(|(i, v)| to_sql_value(i, v))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut batch = Batch::new(conn, sql);
+    let mut stmt = batch
+        .next()
+        .map_err(describe)?
+        .ok_or_else(|| "SQL 为空".to_string())?;
+    if batch.next().map_err(describe)?.is_some() {
+        return Err("一次只能执行一条语句".to_string());
+    }
+    if !stmt.readonly() {
+        return Err("只允许只读语句".to_string());
+    }
+    let mut columns: Vec<QueryColumn> = stmt
+        .columns()
+        .iter()
+        .map(|c| QueryColumn {
+            name: c.name().to_string(),
+            decl_type: c.decl_type().map(String::from),
+            value_type: None,
+        })
+        .collect();
+
+    conn.progress_handler(
+        PROGRESS_OPS,
+        Some(move || started.elapsed() >= QUERY_TIMEOUT),
+    );
+    let result = (|| {
+        let mut rows = Vec::new();
+        let mut truncated = false;
+        let mut cursor = stmt
+            .query(params_from_iter(values.iter()))
+            .map_err(|e| e.to_string())?;
+        while let Some(row) = cursor.next().map_err(|e| e.to_string())? {
+            if rows.len() >= max_rows {
+                truncated = true;
+                break;
+            }
+            let mut out = Vec::with_capacity(columns.len());
+            for (i, col) in columns.iter_mut().enumerate() {
+                let (kind, value) = to_json(row.get_ref(i).map_err(|e| e.to_string())?);
+                col.value_type = match (col.value_type, kind) {
+                    (None, k) => k,
+                    (Some(t), Some(k)) if t != k => Some("mixed"),
+                    (t, _) => t,
+                };
+                out.push(value);
+            }
+            rows.push(out);
+        }
+        Ok((rows, truncated))
+    })();
+    conn.progress_handler(PROGRESS_OPS, None::<fn() -> bool>);
+
+    let (rows, truncated) = result.map_err(|e: String| {
+        if started.elapsed() >= QUERY_TIMEOUT {
+            format!("查询超过 {} 秒已中断", QUERY_TIMEOUT.as_secs())
+        } else {
+            e
+        }
+    })?;
+    Ok(QueryResult {
+        columns,
+        rows,
+        truncated,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// 在只读连接上执行一条查询（SELECT / 只读 PRAGMA / EXPLAIN），与 core 并发。
+/// `params` 按位置绑定 `?`；`maxRows` 缺省 [DEFAULT_MAX_ROWS]，不超过 [MAX_ROWS]。
+#[tauri::command]
+pub async fn db_query(
+    app: AppHandle,
+    sql: String,
+    params: Option<Vec<serde_json::Value>>,
+    max_rows: Option<usize>,
+) -> Result<QueryResult, String> {
+    let max_rows = max_rows.unwrap_or(DEFAULT_MAX_ROWS).clamp(1, MAX_ROWS);
+    blocking(app, move |app| {
+        let conn = open_read_only(&db_path(app)?)?;
+        run(&conn, &sql, &params.unwrap_or_default(), max_rows)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// 建一个含 `t(id, v)` 三行数据的临时数据库，返回只读连接与路径。
+    fn fixture(name: &str) -> (Connection, PathBuf) {
+        let path = std::env::temp_dir().join(format!(
+            "db-query-test-{}-{}.sqlite",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_file(&path);
+        let rw = Connection::open(&path).unwrap();
+        rw.execute_batch(
+            "CREATE TABLE t (id INTEGER PRIMARY KEY, v);
+             INSERT INTO t (v) VALUES (1), ('two'), (NULL);",
+        )
+        .unwrap();
+        drop(rw);
+        (open_read_only(&path).unwrap(), path)
+    }
+
+    #[test]
+    fn select_reports_value_types() {
+        let (conn, _) = fixture("types");
+        let r = run(
+            &conn,
+            "SELECT id, v, NULL AS n, x'0102' AS b FROM t WHERE id <= ?",
+            &[serde_json::json!(3)],
+            MAX_ROWS,
+        )
+        .unwrap();
+        let types: Vec<_> = r.columns.iter().map(|c| c.value_type).collect();
+        assert_eq!(types, [Some("integer"), Some("mixed"), None, Some("blob")]);
+        assert_eq!(r.columns[0].decl_type.as_deref(), Some("INTEGER"));
+        assert_eq!(r.rows.len(), 3);
+        assert_eq!(r.rows[0][3], serde_json::json!("AQI="));
+        assert!(!r.truncated);
+    }
+
+    #[test]
+    fn row_cap_truncates() {
+        let (conn, _) = fixture("cap");
+        let r = run(&conn, "SELECT id FROM t ORDER BY id", &[], 2).unwrap();
+        assert_eq!(r.rows, [[serde_json::json!(1)], [serde_json::json!(2)]]);
+        assert!(r.truncated);
+    }
+
+    #[test]
+    fn large_integers_become_strings() {
+        let (conn, _) = fixture("bigint");
+        let r = run(&conn, "SELECT 9007199254740993", &[], MAX_ROWS).unwrap();
+        assert_eq!(r.rows[0][0], serde_json::json!("9007199254740993"));
+    }
+
+    #[test]
+    fn rejects_writes_and_attach() {
+        let (conn, path) = fixture("writes");
+        let attach = format!("ATTACH DATABASE '{}' AS other", path.display());
+        for sql in [
+            "INSERT INTO t (v) VALUES (4)",
+            "DELETE FROM t",
+            "CREATE TABLE u (x)",
+            attach.as_str(),
+        ] {
+            let err = run(&conn, sql, &[], MAX_ROWS).unwrap_err();
+            assert!(err.contains("只允许只读"), "{}: {}", sql, err);
+        }
+        let count = run(&conn, "SELECT count(*) FROM t", &[], MAX_ROWS).unwrap();
+        assert_eq!(count.rows[0][0], serde_json::json!(3));
+    }
+
+    #[test]
+    fn pragma_allowlist() {
+        let (conn, _) = fixture("pragma");
+        for sql in [
+            "PRAGMA user_version",
+            "PRAGMA table_info(t)",
+            "PRAGMA main.index_list('t')",
+            "PRAGMA quick_check",
+        ] {
+            assert!(run(&conn, sql, &[], MAX_ROWS).is_ok(), "{}", sql);
+        }
+        for sql in [
+            "PRAGMA query_only = OFF",
+            "PRAGMA query_only = 0",
+            "PRAGMA user_version = 7",
+            "PRAGMA journal_mode = DELETE",
+            "PRAGMA wal_checkpoint",
+            "PRAGMA optimize",
+        ] {
+            let err = run(&conn, sql, &[], MAX_ROWS).unwrap_err();
+            assert!(err.contains("只允许只读"), "{}: {}", sql, err);
+        }
+        let r = run(&conn, "PRAGMA query_only", &[], MAX_ROWS).unwrap();
+        assert_eq!(r.rows[0][0], serde_json::json!(1));
+    }
+
+    #[test]
+    fn rejects_multiple_or_empty_statements() {
+        let (conn, _) = fixture("multi");
+        assert_eq!(
+            run(&conn, "SELECT 1; SELECT 2", &[], MAX_ROWS).unwrap_err(),
+            "一次只能执行一条语句"
+        );
+        assert!(run(&conn, "SELECT 1; DROP TABLE t", &[], MAX_ROWS).is_err());
+        assert_eq!(run(&conn, "  ", &[], MAX_ROWS).unwrap_err(), "SQL 为空");
+    }
+
+    #[test]
+    fn rejects_unsupported_params() {
+        let (conn, _) = fixture("params");
+        let err = run(&conn, "SELECT ?", &[serde_json::json!([1])], MAX_ROWS).unwrap_err();
+        assert!(err.starts_with("参数 1"), "{}", err);
+    }
+}
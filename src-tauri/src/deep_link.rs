@@ -0,0 +1,299 @@
+//! `toolbox://` 深链接，如 `toolbox://profile/switch?name=work`、`toolbox://clawbot/install?version=1.2.0`。
+//!
+//! - 只接受 [parse] 中列出的动作，参数逐个校验，缺少、多余或重复的参数一律拒绝；
+//! - 校验通过后 emit `deep-link`（[DeepLinkEvent]），失败时 emit `deep-link-failed`；
+//!   链接可能来自任意网页，Rust 侧不直接执行有副作用的操作，由前端向用户确认后再调用
+//!   `profile_switch` / `clawbot_download` 等命令；
+//! - 前端就绪前到达的链接（如由链接启动应用）先暂存，前端通过 [deep_link_take_pending] 取走后才直接 emit。
+//!
+//! Windows / Linux 上系统以链接为唯一参数启动新进程，由单实例插件转给已运行的实例（见 [crate::instance]）；
+//! macOS 由系统事件送达。两种方式都经插件的 `deep-link://new-url` 事件到达 [handle_url]。
+
+use std::sync::Mutex;
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use crate::clawbot;
+use crate::profile;
+
+pub const SCHEME: &str = "toolbox";
+const MAX_URL_LEN: usize = 2048;
+
+/// 前端就绪前暂存的事件；None 表示前端已就绪，直接 emit。
+static PENDING: Mutex<Option<Vec<PendingEvent>>> = Mutex::new(Some(Vec::new()));
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action")]
+pub enum DeepLinkAction {
+    /// 切换到已存在的 profile
+    #[serde(rename = "profile/switch")]
+    ProfileSwitch { name: String },
+    /// 下载安装 clawbot；不带 version 时为清单中的最新版本
+    #[serde(rename = "clawbot/install")]
+    ClawbotInstall { version: Option<String> },
+}
+
+/// `deep-link` 事件的 payload：`{ action, ...参数, url }`。
+#[derive(Debug, Clone, Serialize)]
+pub struct DeepLinkEvent {
+    #[serde(flatten)]
+    pub action: DeepLinkAction,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingEvent {
+    pub event: &'static str,
+    pub payload: Value,
+}
+
+/// 参数是否为本应用的深链接（单实例转发时据此跳过，交给本模块处理）。
+pub fn is_deep_link(arg: &str) -> bool {
+    arg.strip_prefix(SCHEME)
+        .is_some_and(|rest| rest.starts_with("://"))
+}
+
+/// 查询参数：拒绝重复，取完后拒绝多余的。
+struct Query(Vec<(String, String)>);
+
+impl Query {
+    fn new(url: &Url) -> Result<Self, String> {
+        let mut pairs: Vec<(String, String)> = Vec::new();
+        for (k, v) in url.query_pairs() {
+            if pairs.iter().any(|(n, _)| *n == k) {
+                return Err(format!("参数 {} 重复", k));
+            }
+            pairs.push((k.to_string(), v.to_string()));
+        }
+        Ok(Self(pairs))
+    }
+
+    fn optional(&mut self, name: &str) -> Option<String> {
+        let i = self.0.iter().position(|(n, _)| n == name)?;
+        Some(self.0.remove(i).1).filter(|v| !v.is_empty())
+    }
+
+    fn required(&mut self, name: &str) -> Result<String, String> {
+        self.optional(name)
+            .ok_or_else(|| format!("缺少参数 {}", name))
+    }
+
+    fn finish(self) -> Result<(), String> {
+        match self.0.first() {
+            Some((name, _)) => Err(format!("不支持的参数 {}", name)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 解析链接并校验参数格式；不访问应用状态。
+pub fn parse(url: &str) -> Result<DeepLinkAction, String> {
+    if url.len() > MAX_URL_LEN {
+        return Err("链接过长".to_string());
+    }
+    let url = Url::parse(url).map_err(|e| format!("无法解析链接: {}", e))?;
+    if url.scheme() != SCHEME {
+        return Err(format!("不支持的协议: {}", url.scheme()));
+    }
+    let host = url.host_str().unwrap_or_default();
+    let path = url.path().trim_matches('/');
+    let mut query = Query::new(&url)?;
+    let action = match (host, path) {
+        ("profile", "switch") => {
+            let name = query.required("name")?;
+            profile::check_name(&name)?;
+            DeepLinkAction::ProfileSwitch { name }
+        }
+        ("clawbot", "install") => {
+            let version = query.optional("version");
+            if let Some(v) = &version {
+                clawbot::check_version_name(v)?;
+            }
+            DeepLinkAction::ClawbotInstall { version }
+        }
+        _ => return Err(format!("不支持的操作: {}/{}", host, path)),
+    };
+    query.finish()?;
+    Ok(action)
+}
+
+/// 依赖应用状态的校验。
+fn validate(app: &AppHandle, action: &DeepLinkAction) -> Result<(), String> {
+    match action {
+        DeepLinkAction::ProfileSwitch { name } if !profile::exists(app, name) => {
+            Err(format!("profile {} 不存在", name))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 前端就绪后直接 emit，否则暂存。
+fn deliver(app: &AppHandle, event: &'static str, payload: Value) {
+    let Ok(mut pending) = PENDING.lock() else {
+        return;
+    };
+    match pending.as_mut() {
+        Some(queue) => queue.push(PendingEvent { event, payload }),
+        None => {
+            let _ = app.emit(event, payload);
+        }
+    }
+}
+
+/// 处理一个链接：解析、校验后通知前端。
+pub fn handle_url(app: &AppHandle, url: &str) {
+    let result = parse(url).and_then(|action| validate(app, &action).map(|_| action));
+    match result {
+        Ok(action) => {
+            log::info!("{} -> {:?}", url, action);
+            let event = DeepLinkEvent {
+                action,
+                url: url.to_string(),
+            };
+            if let Ok(payload) = serde_json::to_value(&event) {
+                deliver(app, "deep-link", payload);
+            }
+        }
+        Err(e) => {
+            log::warn!("拒绝 {}: {}", url, e);
+            deliver(
+                app,
+                "deep-link-failed",
+                serde_json::json!({ "url": url, "reason": e }),
+            );
+        }
+    }
+}
+
+/// Setup 阶段调用：处理启动应用的链接，并监听之后到达的链接。
+pub fn init_on_setup(app: &AppHandle) {
+    let deep_link = app.deep_link();
+    // 安装包会注册协议；AppImage 与 Windows 开发构建需要运行时注册
+    #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+    if let Err(e) = deep_link.register_all() {
+        log::warn!("注册 {}:// 协议失败: {}", SCHEME, e);
+    }
+    if let Ok(Some(urls)) = deep_link.get_current() {
+        for url in urls {
+            handle_url(app, url.as_str());
+        }
+    }
+    let handle = app.clone();
+    deep_link.on_open_url(move |event| {
+        for url in event.urls() {
+            handle_url(&handle, url.as_str());
+        }
+    });
+}
+
+/// 取走前端就绪前暂存的 `deep-link` / `deep-link-failed` 事件，此后的链接直接 emit。
+#[tauri::command]
+pub fn deep_link_take_pending() -> Vec<PendingEvent> {
+    PENDING
+        .lock()
+        .ok()
+        .and_then(|mut pending| pending.take())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_supported_actions() {
+        assert_eq!(
+            parse("toolbox://profile/switch?name=work").unwrap(),
+            DeepLinkAction::ProfileSwitch {
+                name: "work".to_string()
+            }
+        );
+        assert_eq!(
+            parse("toolbox://clawbot/install?version=1.2.0").unwrap(),
+            DeepLinkAction::ClawbotInstall {
+                version: Some("1.2.0".to_string())
+            }
+        );
+        assert_eq!(
+            parse("toolbox://clawbot/install/").unwrap(),
+            DeepLinkAction::ClawbotInstall { version: None }
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_scheme_host_or_path() {
+        assert!(parse("https://profile/switch?name=work")
+            .unwrap_err()
+            .contains("不支持的协议"));
+        for url in [
+            "toolbox://profile/delete?name=work",
+            "toolbox://shell/run?cmd=ls",
+            "toolbox://clawbot",
+            "toolbox:///switch?name=work",
+        ] {
+            let err = parse(url).unwrap_err();
+            assert!(err.contains("不支持的操作"), "{}: {}", url, err);
+        }
+        assert!(parse("not a url").is_err());
+    }
+
+    #[test]
+    fn rejects_bad_query() {
+        assert_eq!(
+            parse("toolbox://profile/switch?name=a&name=b").unwrap_err(),
+            "参数 name 重复"
+        );
+        assert_eq!(
+            parse("toolbox://profile/switch?name=work&force=1").unwrap_err(),
+            "不支持的参数 force"
+        );
+        assert_eq!(
+            parse("toolbox://profile/switch").unwrap_err(),
+            "缺少参数 name"
+        );
+        // 空值视为未提供
+        assert_eq!(
+            parse("toolbox://profile/switch?name=").unwrap_err(),
+            "缺少参数 name"
+        );
+        assert_eq!(
+            parse("toolbox://clawbot/install?version=").unwrap(),
+            DeepLinkAction::ClawbotInstall { version: None }
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        for name in ["..%2Fwork", "a%20b", "work%00"] {
+            let url = format!("toolbox://profile/switch?name={}", name);
+            assert!(parse(&url).is_err(), "{}", url);
+        }
+        let long_name = "a".repeat(200);
+        let url = format!("toolbox://profile/switch?name={}", long_name);
+        assert!(parse(&url).is_err());
+        for version in ["..%2F..%2Fetc", ".hidden", "1.0%2F2"] {
+            let url = format!("toolbox://clawbot/install?version={}", version);
+            assert!(parse(&url).unwrap_err().contains("非法的版本号"), "{}", url);
+        }
+    }
+
+    #[test]
+    fn rejects_overlong_url() {
+        let url = format!(
+            "toolbox://clawbot/install?version={}",
+            "1".repeat(MAX_URL_LEN)
+        );
+        assert_eq!(parse(&url).unwrap_err(), "链接过长");
+    }
+
+    #[test]
+    fn recognises_own_scheme() {
+        assert!(is_deep_link("toolbox://profile/switch?name=work"));
+        assert!(!is_deep_link("toolbox:profile"));
+        assert!(!is_deep_link("toolboxx://profile"));
+        assert!(!is_deep_link("--profile"));
+    }
+}
@@ -0,0 +1,125 @@
+//! 无窗口模式（`--headless`）：不创建窗口，只启动 core 与 autostart 服务，供脚本或 CI 使用本地 API。
+//!
+//! - 每次运行生成随机令牌，以 `API_TOKEN` 传给 core；core 对除 `/health` 外的请求校验
+//!   `Authorization: Bearer <token>`（窗口模式不设令牌，行为不变）；
+//! - core 就绪后向 stdout 打印一行 JSON，见 [HeadlessReady]；启动失败时打印 `{"error": "…"}` 并以退出码 1 退出；
+//! - SIGINT / SIGTERM 时走正常退出流程（停止全部服务），超过 [EXIT_GRACE] 仍未退出则强制终止；
+//! - 不注册单实例插件，可与窗口实例并存（建议配合 `--profile` 使用独立数据）。
+//!
+//! Linux 上 Tauri 仍会初始化 GTK，无显示环境的 CI 需要 `xvfb-run`。
+
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::core::CORE_SERVICE_NAME;
+use crate::profile;
+use crate::services::supervisor::{self, Supervisor};
+
+pub const CLI_FLAG: &str = "--headless";
+const TOKEN_BYTES: usize = 32;
+/// 收到退出信号后等待正常退出的时间。
+const EXIT_GRACE: Duration = Duration::from_secs(10);
+
+static TOKEN: OnceLock<Option<String>> = OnceLock::new();
+static APP: OnceLock<AppHandle> = OnceLock::new();
+/// 由 CLI `core start` 开启，等同于带 `--headless`。
+static FORCED: AtomicBool = AtomicBool::new(false);
+
+/// core 就绪后打印到 stdout 的一行 JSON。
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeadlessReady {
+    pub port: u16,
+    pub token: String,
+    pub url: String,
+    pub pid: Option<u32>,
+    pub profile: String,
+}
+
+/// 命令行是否带 `--headless`（或已由 [enable] 开启）。
+pub fn is_enabled() -> bool {
+    FORCED.load(Ordering::Relaxed) || std::env::args().skip(1).any(|a| a == CLI_FLAG)
+}
+
+/// 在 [crate::run] 之前调用，以无窗口模式启动。
+pub fn enable() {
+    FORCED.store(true, Ordering::Relaxed);
+}
+
+fn generate_token() -> Option<String> {
+    let mut bytes = [0u8; TOKEN_BYTES];
+    SystemRandom::new().fill(&mut bytes).ok()?;
+    Some(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// 本次运行的访问令牌；窗口模式为 None。
+pub fn token() -> Option<&'static str> {
+    TOKEN
+        .get_or_init(|| is_enabled().then(generate_token).flatten())
+        .as_deref()
+}
+
+/// 向 stdout 打印一行 JSON 并立即刷新，供脚本逐行读取。
+fn print_line(value: &impl Serialize) {
+    if let Ok(line) = serde_json::to_string(value) {
+        let mut out = std::io::stdout().lock();
+        let _ = writeln!(out, "{}", line);
+        let _ = out.flush();
+    }
+}
+
+/// Setup 阶段调用（仅无窗口模式）：记录 AppHandle 供信号处理使用；macOS 上不显示 Dock 图标。
+pub fn init_on_setup(app: &mut tauri::App) {
+    let _ = APP.set(app.handle().clone());
+    #[cfg(target_os = "macos")]
+    app.set_activation_policy(tauri::ActivationPolicy::Accessory);
+    if token().is_none() {
+        report_failed(app.handle(), "无法生成访问令牌");
+    }
+}
+
+/// core 就绪时调用：打印端口与令牌。
+pub fn report_ready(app: &AppHandle, port: Option<u16>) {
+    let (Some(port), Some(token)) = (port, token()) else {
+        return;
+    };
+    let pid = app
+        .try_state::<Supervisor>()
+        .and_then(|s| s.info(CORE_SERVICE_NAME).ok())
+        .and_then(|info| info.pid);
+    print_line(&HeadlessReady {
+        port,
+        token: token.to_string(),
+        url: format!("http://127.0.0.1:{}", port),
+        pid,
+        profile: profile::active(),
+    });
+}
+
+/// core 无法启动时调用：打印错误并以退出码 1 退出。
+pub fn report_failed(app: &AppHandle, reason: &str) {
+    print_line(&serde_json::json!({ "error": reason }));
+    app.exit(1);
+}
+
+/// 信号处理中调用：无窗口模式下请求正常退出并返回 true；超时仍未退出则强制终止子进程后退出。
+pub fn request_exit() -> bool {
+    let Some(app) = APP.get() else {
+        return false;
+    };
+    log::info!("收到退出信号，正在停止服务...");
+    std::thread::spawn(|| {
+        std::thread::sleep(EXIT_GRACE);
+        log::warn!("等待退出超时，强制终止");
+        supervisor::kill_all_by_pid();
+        std::process::exit(1);
+    });
+    app.exit(0);
+    true
+}
@@ -0,0 +1,273 @@
+//! 单实例：再次启动应用时，参数与工作目录经 tauri-plugin-single-instance 转发给已运行的实例。
+//!
+//! - 支持的命令见 [InstanceAction]：`open <file>`（相对路径按第二个进程的工作目录解析）、
+//!   `run-script <name>`；不带命令或只带 `toolbox://` 链接（见 [crate::deep_link]）时只聚焦主窗口；
+//! - 第二个进程在转发前先用 [parse] 校验（见 [crate::cli]），不合法时以退出码 1 结束、不再转发；
+//!   校验通过后由插件转发，退出码为 0；
+//! - 主实例聚焦窗口后 emit `second-instance`（[SecondInstance]），解析失败时 emit `second-instance-failed`；
+//! - 首次启动就带命令时没有监听者，动作暂存起来，前端就绪后通过 [instance_launch_action] 取走。
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::deep_link;
+use crate::headless;
+use crate::profile;
+
+pub const OPEN: &str = "open";
+pub const RUN_SCRIPT: &str = "run-script";
+/// 转发给主实例的命令（第一个位置参数）。
+pub const COMMANDS: &[&str] = &[OPEN, RUN_SCRIPT];
+const MAX_SCRIPT_NAME_LEN: usize = 64;
+
+/// 首次启动时带的命令，见 [init_on_setup]。
+static LAUNCH: Mutex<Option<SecondInstance>> = Mutex::new(None);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub enum InstanceAction {
+    /// 不带命令：只聚焦窗口
+    Focus,
+    /// 打开文件（绝对路径，已确认存在）
+    Open { path: String },
+    /// 运行脚本
+    RunScript { name: String },
+}
+
+/// `second-instance` 事件的 payload：`{ action, path? / name?, args, cwd }`。
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecondInstance {
+    #[serde(flatten)]
+    pub action: InstanceAction,
+    /// 不含程序路径的原始参数
+    pub args: Vec<String>,
+    pub cwd: String,
+}
+
+/// 去掉 `--profile <name>`、`--headless` 等选项后的位置参数。
+fn positional(args: &[String]) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == profile::CLI_FLAG {
+            iter.next();
+        } else if !arg.starts_with('-') {
+            out.push(arg.as_str());
+        }
+    }
+    out
+}
+
+/// 第一个位置参数是否为 [COMMANDS] 之一。
+pub fn is_command(args: &[String]) -> bool {
+    positional(args)
+        .first()
+        .is_some_and(|w| COMMANDS.contains(w))
+}
+
+fn check_script_name(name: &str) -> Result<(), String> {
+    let ok = !name.is_empty()
+        && name.len() <= MAX_SCRIPT_NAME_LEN
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if ok {
+        Ok(())
+    } else {
+        Err(format!(
+            "非法的脚本名: {}（仅限 {} 个以内的字母、数字、-、_ 和 .）",
+            name, MAX_SCRIPT_NAME_LEN
+        ))
+    }
+}
+
+/// 相对路径按 `cwd` 解析，须为已存在的文件。
+fn resolve_file(cwd: &Path, file: &str) -> Result<String, String> {
+    let path = cwd.join(file);
+    if !path.is_file() {
+        return Err(format!("文件不存在: {}", path.display()));
+    }
+    let path = path.canonicalize().map_err(|e| e.to_string())?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// 解析参数（不含程序路径）；`cwd` 为发起启动的进程的工作目录。
+pub fn parse(args: &[String], cwd: &Path) -> Result<InstanceAction, String> {
+    match positional(args).as_slice() {
+        [] => Ok(InstanceAction::Focus),
+        // 深链接由 deep-link 插件转交 crate::deep_link 处理，这里只聚焦窗口
+        [url] if deep_link::is_deep_link(url) => Ok(InstanceAction::Focus),
+        [OPEN, file] => Ok(InstanceAction::Open {
+            path: resolve_file(cwd, file)?,
+        }),
+        [RUN_SCRIPT, name] => {
+            check_script_name(name)?;
+            Ok(InstanceAction::RunScript {
+                name: name.to_string(),
+            })
+        }
+        [OPEN, ..] => Err(format!("用法: {} <file>", OPEN)),
+        [RUN_SCRIPT, ..] => Err(format!("用法: {} <name>", RUN_SCRIPT)),
+        words => Err(format!("未知命令: {}", words.join(" "))),
+    }
+}
+
+fn focus_main_window(app: &AppHandle) {
+    if let Some(w) = app.get_webview_window("main") {
+        let _ = w.unminimize();
+        let _ = w.show();
+        let _ = w.set_focus();
+    }
+}
+
+/// 单实例插件回调：`argv` 为第二个进程的完整参数（含程序路径），`cwd` 为其工作目录。
+pub fn on_second_instance(app: &AppHandle, argv: Vec<String>, cwd: String) {
+    focus_main_window(app);
+    let args: Vec<String> = argv.into_iter().skip(1).collect();
+    match parse(&args, Path::new(&cwd)) {
+        Ok(action) => {
+            log::info!("收到第二次启动的命令: {:?}", action);
+            let _ = app.emit("second-instance", SecondInstance { action, args, cwd });
+        }
+        Err(e) => {
+            log::warn!("忽略第二次启动的参数 {:?}: {}", args, e);
+            let _ = app.emit(
+                "second-instance-failed",
+                serde_json::json!({ "args": args, "cwd": cwd, "reason": e }),
+            );
+        }
+    }
+}
+
+/// Setup 阶段调用：首次启动就带命令时暂存，待前端取走。无窗口模式不处理。
+pub fn init_on_setup() {
+    if headless::is_enabled() {
+        return;
+    }
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let cwd = std::env::current_dir().unwrap_or_default();
+    match parse(&args, &cwd) {
+        Ok(InstanceAction::Focus) => {}
+        Ok(action) => {
+            log::info!("启动命令: {:?}", action);
+            if let Ok(mut slot) = LAUNCH.lock() {
+                *slot = Some(SecondInstance {
+                    action,
+                    args,
+                    cwd: cwd.to_string_lossy().to_string(),
+                });
+            }
+        }
+        Err(e) => log::warn!("忽略启动参数 {:?}: {}", args, e),
+    }
+}
+
+/// 取走首次启动时带的命令（只返回一次）；之后的命令经 `second-instance` 事件送达。
+#[tauri::command]
+pub fn instance_launch_action() -> Option<SecondInstance> {
+    LAUNCH.lock().ok().and_then(|mut slot| slot.take())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    /// 含一个 `notes.txt` 的临时工作目录。
+    fn temp_cwd() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("instance-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("notes.txt"), b"hi").unwrap();
+        dir
+    }
+
+    #[test]
+    fn focus_without_command_or_with_deep_link() {
+        let cwd = temp_cwd();
+        assert_eq!(parse(&[], &cwd).unwrap(), InstanceAction::Focus);
+        let link = args(&["toolbox://profile/switch?name=work"]);
+        assert_eq!(parse(&link, &cwd).unwrap(), InstanceAction::Focus);
+    }
+
+    #[test]
+    fn open_resolves_relative_to_cwd() {
+        let cwd = temp_cwd();
+        let expected = cwd.join("notes.txt").canonicalize().unwrap();
+        assert_eq!(
+            parse(&args(&["open", "notes.txt"]), &cwd).unwrap(),
+            InstanceAction::Open {
+                path: expected.to_string_lossy().to_string()
+            }
+        );
+        let err = parse(&args(&["open", "missing.txt"]), &cwd).unwrap_err();
+        assert!(err.starts_with("文件不存在"), "{}", err);
+        // 目录不是文件
+        assert!(parse(&args(&["open", "."]), &cwd).is_err());
+        assert_eq!(
+            parse(&args(&["open"]), &cwd).unwrap_err(),
+            "用法: open <file>"
+        );
+        assert_eq!(
+            parse(&args(&["open", "a", "b"]), &cwd).unwrap_err(),
+            "用法: open <file>"
+        );
+    }
+
+    #[test]
+    fn run_script_checks_name() {
+        let cwd = temp_cwd();
+        assert_eq!(
+            parse(&args(&["run-script", "build.prod"]), &cwd).unwrap(),
+            InstanceAction::RunScript {
+                name: "build.prod".to_string()
+            }
+        );
+        let long = "a".repeat(MAX_SCRIPT_NAME_LEN + 1);
+        for name in ["", ".hidden", "../x", "a b", "a/b", long.as_str()] {
+            let err = parse(&args(&["run-script", name]), &cwd).unwrap_err();
+            assert!(err.starts_with("非法的脚本名"), "{:?}: {}", name, err);
+        }
+        assert_eq!(
+            parse(&args(&["run-script"]), &cwd).unwrap_err(),
+            "用法: run-script <name>"
+        );
+    }
+
+    #[test]
+    fn skips_options_and_profile_value() {
+        let cwd = temp_cwd();
+        let list = args(&["--profile", "open", "--headless", "run-script", "x"]);
+        // `--profile` 的值 "open" 不是命令
+        assert_eq!(positional(&list), vec!["run-script", "x"]);
+        assert_eq!(
+            parse(&list, &cwd).unwrap(),
+            InstanceAction::RunScript {
+                name: "x".to_string()
+            }
+        );
+        assert!(is_command(&list));
+        assert!(!is_command(&args(&["--profile", "run-script"])));
+        // 末尾的 `--profile` 没有值时不越界
+        assert_eq!(positional(&args(&["--profile"])), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn rejects_unknown_command() {
+        let cwd = temp_cwd();
+        assert_eq!(
+            parse(&args(&["delete", "everything"]), &cwd).unwrap_err(),
+            "未知命令: delete everything"
+        );
+        assert!(!is_command(&args(&["delete"])));
+    }
+}
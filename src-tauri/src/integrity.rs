@@ -0,0 +1,244 @@
+//! core 包完整性校验：每次启动 core 前，按包内 `integrity.json` 逐个比对 SHA-256，
+//! 避免执行被篡改或只写了一半的 core。
+//!
+//! `integrity.json` 由 core 构建（`core/tsup.config.ts` 的 onSuccess）在 `resources/core` 生成：
+//!
+//! ```json
+//! { "algorithm": "sha256", "files": { "index.js": "…", "node_modules/fastify/package.json": "…" } }
+//! ```
+//!
+//! 路径相对 core 目录、以 `/` 分隔；符号链接不参与校验。清单与被校验的文件放在同一目录，
+//! 本身并不可信，因此先比对清单的 SHA-256：内置 core 用构建时由 `build.rs` 嵌入的
+//! `CORE_INTEGRITY_SHA256`，更新包用安装时（签名校验通过后）记录在 `state.json` 的摘要
+//! （见 `updater.rs`）。能改写 state.json 的一方同样能改写更新包，这一层只防目录内的篡改。
+//!
+//! 清单缺失或摘要不符、文件缺失、哈希不一致或出现清单外的文件都算校验失败，
+//! 按 settings.json 的 `core_integrity_policy` 处理：
+//!
+//! - `enforce`：拒绝启动（release 构建的缺省值）；
+//! - `warn`：记录日志后照常启动（debug 构建的缺省值）；
+//! - `off`：不校验。
+//!
+//! 失败时（`off` 除外）向前端 emit `core-integrity-failed`，负载见 [IntegrityEvent]。
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::config;
+use crate::updater;
+use crate::util::{sha256_file, sha256_hex};
+
+pub const MANIFEST_FILE_NAME: &str = "integrity.json";
+/// 构建时嵌入的内置 core 清单摘要；构建时清单尚未生成则为空串。
+const EMBEDDED_MANIFEST_SHA256: &str = env!("CORE_INTEGRITY_SHA256");
+/// 事件与日志中每类最多列出的文件数。
+const REPORT_LIMIT: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IntegrityPolicy {
+    Enforce,
+    Warn,
+    Off,
+}
+
+impl IntegrityPolicy {
+    pub fn from_config(app: &AppHandle) -> Self {
+        match config::get_core_integrity_policy(app).as_deref() {
+            Some("enforce") => Self::Enforce,
+            Some("warn") => Self::Warn,
+            Some("off") => Self::Off,
+            _ if cfg!(debug_assertions) => Self::Warn,
+            _ => Self::Enforce,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct IntegrityManifest {
+    algorithm: String,
+    files: BTreeMap<String, String>,
+}
+
+/// 一次校验的结果；各列表均为相对路径。
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReport {
+    pub checked: usize,
+    pub mismatched: Vec<String>,
+    pub missing: Vec<String>,
+    pub unexpected: Vec<String>,
+    /// 清单本身缺失或无法解析
+    pub error: Option<String>,
+}
+
+impl IntegrityReport {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+            && self.mismatched.is_empty()
+            && self.missing.is_empty()
+            && self.unexpected.is_empty()
+    }
+
+    fn summary(&self) -> String {
+        if let Some(e) = &self.error {
+            return e.clone();
+        }
+        format!(
+            "{} 个文件不一致，{} 个缺失，{} 个多余",
+            self.mismatched.len(),
+            self.missing.len(),
+            self.unexpected.len()
+        )
+    }
+
+    fn truncate(&mut self) {
+        self.mismatched.truncate(REPORT_LIMIT);
+        self.missing.truncate(REPORT_LIMIT);
+        self.unexpected.truncate(REPORT_LIMIT);
+    }
+}
+
+/// `core-integrity-failed` 事件负载。
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityEvent {
+    pub core_dir: String,
+    pub policy: IntegrityPolicy,
+    /// 是否因此拒绝启动
+    pub refused: bool,
+    pub report: IntegrityReport,
+}
+
+/// 递归列出 `dir` 下的普通文件（相对路径，`/` 分隔），跳过符号链接。
+fn collect_files(root: &Path, dir: &Path, out: &mut Vec<String>) -> Result<(), String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("读取 {} 失败: {}", dir.display(), e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(meta) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if meta.is_dir() {
+            collect_files(root, &path, out)?;
+        } else if meta.is_file() {
+            if let Ok(rel) = path.strip_prefix(root) {
+                let rel: Vec<String> = rel
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy().to_string())
+                    .collect();
+                out.push(rel.join("/"));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 按 `core_dir/integrity.json` 校验整个目录；清单本身须与 `manifest_sha256` 一致。
+pub fn verify_dir(core_dir: &Path, manifest_sha256: &str) -> IntegrityReport {
+    let mut report = IntegrityReport::default();
+    let manifest_path = core_dir.join(MANIFEST_FILE_NAME);
+    let Ok(bytes) = fs::read(&manifest_path) else {
+        report.error = Some(format!(
+            "缺少 {}（请重新执行 pnpm -C core run build）",
+            MANIFEST_FILE_NAME
+        ));
+        return report;
+    };
+    if manifest_sha256.is_empty() {
+        report.error = Some(format!(
+            "没有可信的 {} 摘要（内置 core 须先构建再编译应用，更新包须重新安装）",
+            MANIFEST_FILE_NAME
+        ));
+        return report;
+    }
+    let digest = sha256_hex(&bytes);
+    if !digest.eq_ignore_ascii_case(manifest_sha256) {
+        report.error = Some(format!("{} 与可信摘要不一致", MANIFEST_FILE_NAME));
+        return report;
+    }
+    let manifest: IntegrityManifest = match serde_json::from_slice(&bytes) {
+        Ok(m) => m,
+        Err(e) => {
+            report.error = Some(format!("{} 格式错误: {}", MANIFEST_FILE_NAME, e));
+            return report;
+        }
+    };
+    if !manifest.algorithm.eq_ignore_ascii_case("sha256") {
+        report.error = Some(format!("不支持的算法: {}", manifest.algorithm));
+        return report;
+    }
+
+    let mut actual = Vec::new();
+    if let Err(e) = collect_files(core_dir, core_dir, &mut actual) {
+        report.error = Some(e);
+        return report;
+    }
+    for rel in &actual {
+        if rel == MANIFEST_FILE_NAME {
+            continue;
+        }
+        let Some(expected) = manifest.files.get(rel) else {
+            report.unexpected.push(rel.clone());
+            continue;
+        };
+        report.checked += 1;
+        match sha256_file(&core_dir.join(rel)) {
+            Ok(hash) if hash.eq_ignore_ascii_case(expected) => {}
+            _ => report.mismatched.push(rel.clone()),
+        }
+    }
+    let present: HashSet<&String> = actual.iter().collect();
+    report.missing = manifest
+        .files
+        .keys()
+        .filter(|rel| !present.contains(rel))
+        .cloned()
+        .collect();
+    report
+}
+
+/// 按策略校验 `core_dir`：通过或仅警告时返回 Ok，`enforce` 下失败返回 Err。
+pub fn check_core_dir(app: &AppHandle, core_dir: &Path) -> Result<(), String> {
+    let policy = IntegrityPolicy::from_config(app);
+    if policy == IntegrityPolicy::Off {
+        return Ok(());
+    }
+    let trusted = updater::trusted_manifest_digest(app, core_dir)
+        .unwrap_or_else(|| EMBEDDED_MANIFEST_SHA256.to_string());
+    let mut report = verify_dir(core_dir, &trusted);
+    if report.is_ok() {
+        log::info!("完整性校验通过（{} 个文件）", report.checked);
+        return Ok(());
+    }
+
+    let summary = report.summary();
+    let refused = policy == IntegrityPolicy::Enforce;
+    log::error!(
+        "完整性校验失败（{}）: {} | {}",
+        if refused { "拒绝启动" } else { "仅警告" },
+        core_dir.display(),
+        summary
+    );
+    for rel in report.mismatched.iter().take(REPORT_LIMIT) {
+        log::warn!("不一致: {}", rel);
+    }
+    report.truncate();
+    let _ = app.emit(
+        "core-integrity-failed",
+        IntegrityEvent {
+            core_dir: core_dir.to_string_lossy().to_string(),
+            policy,
+            refused,
+            report,
+        },
+    );
+    if refused {
+        Err(format!("core 完整性校验失败: {}", summary))
+    } else {
+        Ok(())
+    }
+}
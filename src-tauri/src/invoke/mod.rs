@@ -73,6 +73,50 @@ macro_rules! invoke_handler {
             $crate::invoke::run_node_runtime,
             $crate::store::store_read,
             $crate::store::store_write,
+            $crate::store::store_entries,
+            $crate::store::store_encrypt_migrate,
+            $crate::store::backup::store_export,
+            $crate::store::backup::store_import,
+            $crate::store::backup::store_list_snapshots,
+            $crate::store::backup::store_restore_snapshot,
+            $crate::clawbot::clawbot_check_state,
+            $crate::clawbot::download::clawbot_download,
+            $crate::clawbot::versions::clawbot_list_versions,
+            $crate::clawbot::versions::clawbot_activate,
+            $crate::clawbot::versions::clawbot_rollback,
+            $crate::clawbot::service::clawbot_start,
+            $crate::clawbot::service::clawbot_stop,
+            $crate::clawbot::service::clawbot_status,
+            $crate::services::services_list,
+            $crate::services::service_start,
+            $crate::services::service_stop,
+            $crate::services::service_restart,
+            $crate::services::service_logs,
+            $crate::services::services_manifests,
+            $crate::services::services_reload,
+            $crate::updater::core_update_check,
+            $crate::updater::core_update_install,
+            $crate::updater::core_update_rollback,
+            $crate::db::db_migrations,
+            $crate::db::db_location,
+            $crate::db::backup::db_list_backups,
+            $crate::db::backup::db_backup,
+            $crate::db::backup::db_restore,
+            $crate::db::maintenance::db_integrity_check,
+            $crate::db::maintenance::db_vacuum,
+            $crate::db::maintenance::db_analyze,
+            $crate::db::maintenance::db_stats,
+            $crate::db::query::db_query,
+            $crate::profile::profile_list,
+            $crate::profile::profile_current,
+            $crate::profile::profile_create,
+            $crate::profile::profile_clone,
+            $crate::profile::profile_delete,
+            $crate::profile::profile_switch,
+            $crate::instance::instance_launch_action,
+            $crate::deep_link::deep_link_take_pending,
+            $crate::logging::logs_query,
+            $crate::logging::logs_info,
         ]
     };
 }
@@ -1,49 +1,102 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 use tauri::Manager;
+mod clawbot;
+mod cli;
 mod config;
 mod core;
+mod db;
+mod deep_link;
+mod headless;
+mod instance;
+mod integrity;
 mod invoke;
+mod logging;
+mod profile;
+mod services;
 mod store;
+mod updater;
+mod util;
+
+/// 应用上下文；`windowed` 为 false（无窗口模式与 CLI）时不创建 tauri.conf.json 中的窗口。
+fn context(windowed: bool) -> tauri::Context<tauri::Wry> {
+    let mut context = tauri::generate_context!();
+    if !windowed {
+        context.config_mut().app.windows.clear();
+    }
+    context
+}
+
+/// 命令行子命令（见 [cli]），须在 [run] 之前调用；返回 Some 时以该退出码结束进程。
+pub fn run_cli() -> Option<i32> {
+    cli::run()
+}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    // Ctrl+C 时先终止 Node 侧车再退出，避免 Drop 来不及执行导致侧车残留
+    logging::init();
+    let headless = headless::is_enabled();
+    // Ctrl+C / SIGTERM 时先终止 Node 侧车与 Clawbot 再退出，避免 Drop 来不及执行导致子进程残留；
+    // 无窗口模式下走正常退出流程
     let _ = ctrlc::set_handler(|| {
-        core::kill_sidecar_by_pid();
+        if headless::request_exit() {
+            return;
+        }
+        services::supervisor::kill_all_by_pid();
         std::process::exit(0);
     });
 
-    tauri::Builder::default()
-        .plugin(tauri_plugin_store::Builder::new().build())
-        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
-            if let Some(w) = app.get_webview_window("main") {
-                let _ = w.show();
-                let _ = w.set_focus();
-            }
-        }))
+    let mut builder = tauri::Builder::default().plugin(tauri_plugin_store::Builder::new().build());
+    if !headless {
+        // 单实例插件须最先注册；开启 deep-link feature 后转发来的 toolbox:// 链接交给深链接插件
+        builder = builder
+            .plugin(tauri_plugin_single_instance::init(
+                instance::on_second_instance,
+            ))
+            .plugin(tauri_plugin_deep_link::init());
+    }
+    builder
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_sql::Builder::default().build())
         .manage(core::CorePorts::default())
-        .manage(core::CoreSidecarChild::default())
-        .setup(|app| {
-            #[cfg(not(any(target_os = "android", target_os = "ios")))]
-            {
-                let skip = std::env::var("TAURI_SKIP_SIDECAR").as_deref() == Ok("1");
-                if skip {
-                    eprintln!("[core] TAURI_SKIP_SIDECAR=1，不启动 Node 服务，已写入 .env 供自启");
-                    core::write_core_env_when_skip(app.handle());
-                } else {
-                    eprintln!("[core] 准备启动 Node 服务...");
-                    if let Err(e) = core::start_core_on_setup(app.handle()) {
-                        eprintln!("[core] 启动失败: {}", e);
-                    }
-                }
+        .manage(services::supervisor::Supervisor::default())
+        .manage(services::user::UserServices::default())
+        .setup(move |app| {
+            logging::init_on_setup(app.handle());
+            if headless {
+                headless::init_on_setup(app);
+            }
+            instance::init_on_setup();
+            if !headless {
+                deep_link::init_on_setup(app.handle());
             }
+            // 先确定 profile：之后的 store、数据库与 core 环境都指向它的数据目录
+            profile::init_on_setup(app.handle());
+            // 先迁移 store 文件，再让插件 / 前端加载它
+            store::migrate::run_on_setup(app.handle());
+            app.manage(store::schema::StoreSchemas::load(app.handle()));
+            store::ttl::spawn_sweeper(app.handle());
+            // 数据库结构迁移须在 core 启动前完成；失败时 core 不启动
+            db::migrate::run_on_setup(app.handle());
+            db::backup::spawn_scheduler(app.handle());
+            clawbot::reconcile_on_setup(app.handle());
+            clawbot::service::register_on_setup(app.handle());
+            #[cfg(not(any(target_os = "android", target_os = "ios")))]
+            core::start_on_setup(app.handle());
+            // 用户服务在 core 之后注册，清单中的 dependsOn / requiredBy 才能引用 core
+            services::user::load_on_setup(app.handle());
+            // 按依赖顺序在后台启动 core 与 autostart 用户服务
+            services::supervisor::spawn_autostart(app.handle());
             Ok(())
         })
         .invoke_handler(invoke_handler!())
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(context(!headless))
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                core::clear_runtime(app);
+                services::supervisor::stop_all(app);
+            }
+        });
 }
@@ -0,0 +1,599 @@
+//! Rust 侧日志：`log` 宏 → stderr（人读）+ `<app_log_dir>/app.log`（JSON Lines，按大小轮转）。
+//!
+//! - 级别：环境变量 `LOG_LEVEL`，其次 settings.json 的 `log_level`，默认 info（debug 构建为 debug）；
+//!   本 crate 按该级别输出，第三方 crate 最多输出到 warn；同一级别经 `LOG_LEVEL` 传给 core（见 [level_name]）；
+//! - target 为去掉 crate 名的模块路径（如 `core`、`db::backup`），文件中每行为
+//!   `{"time","level","target","msg"}`，`time` 与 core 的 pino 日志同为 UTC ISO 8601；
+//! - `app.log` 超过 [MAX_FILE_BYTES] 时依次轮转为 `app.1.log` … `app.<KEEP_FILES>.log`；
+//! - setup 之前的记录先缓存在内存，日志目录确定后补写；CLI（见 [crate::cli]）只输出到 stderr；
+//! - [logs_query] 把本文件与当前 profile 下 core 的 pino 日志（见 [crate::core::daily_log_files]）合并为一条时间线。
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use log::{LevelFilter, Log, Metadata, Record};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Manager};
+
+use crate::config;
+use crate::core;
+use crate::util;
+
+const LOG_FILE_NAME: &str = "app.log";
+/// 单个日志文件上限，超过后轮转。
+const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// 保留的轮转文件份数（不含当前文件）。
+const KEEP_FILES: usize = 5;
+/// setup 之前最多缓存的记录条数。
+const MAX_PENDING: usize = 1000;
+const LEVEL_ENV: &str = "LOG_LEVEL";
+const DEFAULT_QUERY_LIMIT: usize = 500;
+const MAX_QUERY_LIMIT: usize = 5000;
+const DAY_MS: i64 = 86_400_000;
+pub const SOURCE_APP: &str = "app";
+pub const SOURCE_CORE: &str = "core";
+
+enum Sink {
+    /// 日志目录未确定，先缓存
+    Pending(Vec<String>),
+    File {
+        path: PathBuf,
+        file: File,
+        size: u64,
+    },
+    /// 只输出到 stderr（CLI，或日志文件无法打开）
+    Stderr,
+}
+
+struct Logger {
+    sink: Mutex<Sink>,
+}
+
+static LOGGER: Logger = Logger {
+    sink: Mutex::new(Sink::Pending(Vec::new())),
+};
+
+/// 本 crate 的记录去掉 crate 名前缀；第三方 crate 返回 None。
+fn own_target(target: &str) -> Option<&str> {
+    let rest = target.strip_prefix(env!("CARGO_CRATE_NAME"))?;
+    match rest.strip_prefix("::") {
+        Some(t) => Some(t),
+        None if rest.is_empty() => Some("app"),
+        None => None,
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let limit = if own_target(metadata.target()).is_some() {
+            log::max_level()
+        } else {
+            log::max_level().min(LevelFilter::Warn)
+        };
+        metadata.level() <= limit
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let time = iso_utc(util::now_millis() as i64);
+        let target = own_target(record.target()).unwrap_or(record.target());
+        let msg = record.args().to_string();
+        eprintln!("{} {:<5} [{}] {}", time, record.level(), target, msg);
+        let line = serde_json::json!({
+            "time": time,
+            "level": record.level().as_str().to_ascii_lowercase(),
+            "target": target,
+            "msg": msg,
+        })
+        .to_string();
+        if let Ok(mut sink) = self.sink.lock() {
+            sink.write_line(line);
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut sink) = self.sink.lock() {
+            if let Sink::File { file, .. } = &mut *sink {
+                let _ = file.flush();
+            }
+        }
+    }
+}
+
+impl Sink {
+    fn write_line(&mut self, line: String) {
+        match self {
+            Sink::Pending(lines) => {
+                if lines.len() < MAX_PENDING {
+                    lines.push(line);
+                }
+            }
+            Sink::File { path, file, size } => {
+                let len = line.len() as u64 + 1;
+                if *size > 0 && *size + len > MAX_FILE_BYTES {
+                    match rotate(path) {
+                        Ok(f) => {
+                            *file = f;
+                            *size = 0;
+                        }
+                        Err(e) => eprintln!("[logging] 轮转 {} 失败: {}", path.display(), e),
+                    }
+                }
+                if writeln!(file, "{}", line).is_ok() {
+                    *size += len;
+                }
+            }
+            Sink::Stderr => {}
+        }
+    }
+}
+
+/// `app.log` → `app.<n>.log`
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!("{}.{}.log", stem, n))
+}
+
+/// 依次后移轮转文件（最旧的被覆盖），当前文件改名为 `.1`，返回新打开的当前文件。
+fn rotate(path: &Path) -> std::io::Result<File> {
+    for n in (1..KEEP_FILES).rev() {
+        let from = rotated_path(path, n);
+        if from.exists() {
+            fs::rename(&from, rotated_path(path, n + 1))?;
+        }
+    }
+    fs::rename(path, rotated_path(path, 1))?;
+    open_append(path)
+}
+
+fn open_append(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// 解析级别名，兼容 pino 的 `fatal` / `silent`。
+fn parse_level(name: &str) -> Option<LevelFilter> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "fatal" => Some(LevelFilter::Error),
+        "silent" => Some(LevelFilter::Off),
+        other => other.parse().ok(),
+    }
+}
+
+fn default_level() -> LevelFilter {
+    if cfg!(debug_assertions) {
+        LevelFilter::Debug
+    } else {
+        LevelFilter::Info
+    }
+}
+
+fn env_level() -> Option<LevelFilter> {
+    std::env::var(LEVEL_ENV).ok().and_then(|v| parse_level(&v))
+}
+
+/// 当前级别的 pino 名称，作为 core 的 `LOG_LEVEL`。
+pub fn level_name() -> String {
+    match log::max_level() {
+        LevelFilter::Off => "silent".to_string(),
+        level => level.as_str().to_ascii_lowercase(),
+    }
+}
+
+/// 尽早调用（`run` 与 CLI 入口）：安装 logger，级别取 `LOG_LEVEL` 或默认值；重复调用无副作用。
+pub fn init() {
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(env_level().unwrap_or_else(default_level));
+    }
+}
+
+/// CLI 不写日志文件，只输出到 stderr；未设置 `LOG_LEVEL` 时只输出 warn 及以上。
+pub fn init_for_cli() {
+    init();
+    log::set_max_level(env_level().unwrap_or(LevelFilter::Warn));
+    if let Ok(mut sink) = LOGGER.sink.lock() {
+        *sink = Sink::Stderr;
+    }
+}
+
+/// Setup 阶段调用：按配置确定级别，打开 `<app_log_dir>/app.log` 并补写之前缓存的记录。
+pub fn init_on_setup(app: &AppHandle) {
+    let level = env_level()
+        .or_else(|| config::get_log_level(app).and_then(|l| parse_level(&l)))
+        .unwrap_or_else(default_level);
+    log::set_max_level(level);
+    let opened = log_path(app).and_then(|path| {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let file = open_append(&path).map_err(|e| e.to_string())?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Sink::File { path, file, size })
+    });
+    let Ok(mut sink) = LOGGER.sink.lock() else {
+        return;
+    };
+    let pending = match std::mem::replace(&mut *sink, Sink::Stderr) {
+        Sink::Pending(lines) => lines,
+        _ => Vec::new(),
+    };
+    match opened {
+        Ok(file_sink) => {
+            *sink = file_sink;
+            for line in pending {
+                sink.write_line(line);
+            }
+        }
+        Err(e) => eprintln!("[logging] 无法打开日志文件，仅输出到 stderr: {}", e),
+    }
+}
+
+/// `<app_log_dir>/app.log`
+pub fn log_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_log_dir().map_err(|e| e.to_string())?;
+    Ok(dir.join(LOG_FILE_NAME))
+}
+
+// ---------------------------------------------------------------------------
+// 时间格式（UTC ISO 8601，与 pino 的 isoTime 一致）
+// ---------------------------------------------------------------------------
+
+/// 公历日期 ↔ 自 1970-01-01 起的天数（Howard Hinnant 的 civil_from_days / days_from_civil）。
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    (y, m, d)
+}
+
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = (m + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Unix 毫秒 → `YYYY-MM-DDTHH:MM:SS.mmmZ`
+fn iso_utc(ms: i64) -> String {
+    let secs = ms.div_euclid(1000);
+    let (y, m, d) = civil_from_days(secs.div_euclid(86_400));
+    let sod = secs.rem_euclid(86_400);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        y,
+        m,
+        d,
+        sod / 3600,
+        sod % 3600 / 60,
+        sod % 60,
+        ms.rem_euclid(1000)
+    )
+}
+
+/// `YYYY-MM-DDTHH:MM:SS[.f+]Z` → Unix 毫秒；其他格式返回 None。
+fn parse_iso_utc(s: &str) -> Option<i64> {
+    let num = |range: std::ops::Range<usize>| -> Option<i64> {
+        let part = s.get(range)?;
+        part.bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| part.parse().ok())
+            .flatten()
+    };
+    let b = s.as_bytes();
+    if b.len() < 20
+        || b[4] != b'-'
+        || b[7] != b'-'
+        || b[10] != b'T'
+        || b[13] != b':'
+        || b[16] != b':'
+    {
+        return None;
+    }
+    let rest = s.get(19..)?.strip_suffix('Z')?;
+    let millis = match rest.strip_prefix('.') {
+        Some(frac) if !frac.is_empty() && frac.bytes().all(|b| b.is_ascii_digit()) => {
+            format!("{:0<3}", &frac[..frac.len().min(3)])
+                .parse::<i64>()
+                .ok()?
+        }
+        Some(_) => return None,
+        None if rest.is_empty() => 0,
+        None => return None,
+    };
+    let days = days_from_civil(num(0..4)?, num(5..7)?, num(8..10)?);
+    let secs = days * 86_400 + num(11..13)? * 3600 + num(14..16)? * 60 + num(17..19)?;
+    Some(secs * 1000 + millis)
+}
+
+// ---------------------------------------------------------------------------
+// 查询
+// ---------------------------------------------------------------------------
+
+/// [logs_query] 的过滤条件，全部可选。
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LogQuery {
+    /// 起始时间（Unix 毫秒，含）
+    pub since: Option<i64>,
+    /// 结束时间（Unix 毫秒，含）
+    pub until: Option<i64>,
+    /// 最低级别：trace / debug / info / warn / error
+    pub level: Option<String>,
+    /// [SOURCE_APP] 或 [SOURCE_CORE]；为空时合并两者
+    pub source: Option<String>,
+    /// target 前缀，如 `core`、`db`（仅 Rust 侧记录有 target）
+    pub target: Option<String>,
+    /// 消息中包含的文本（不区分大小写）
+    pub contains: Option<String>,
+    /// 最多返回的条数（取最新的），默认 [DEFAULT_QUERY_LIMIT]，上限 [MAX_QUERY_LIMIT]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    /// Unix 毫秒
+    pub time: i64,
+    pub level: String,
+    /// [SOURCE_APP] 或 [SOURCE_CORE]
+    pub source: &'static str,
+    pub target: Option<String>,
+    pub msg: String,
+    /// 其余字段（如 pino 的 reqId、err）
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    pub fields: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogQueryResult {
+    /// 按时间升序
+    pub entries: Vec<LogEntry>,
+    /// 符合条件的记录多于 limit，较早的已省略
+    pub truncated: bool,
+}
+
+/// 级别排序；pino 的 fatal 高于 error。
+fn level_rank(name: &str) -> u8 {
+    match name {
+        "trace" => 0,
+        "debug" => 1,
+        "info" => 2,
+        "warn" => 3,
+        "error" => 4,
+        "fatal" => 5,
+        _ => 2,
+    }
+}
+
+/// pino 数字级别 → 名称。
+fn pino_level_name(n: u64) -> &'static str {
+    match n {
+        0..=14 => "trace",
+        15..=24 => "debug",
+        25..=34 => "info",
+        35..=44 => "warn",
+        45..=54 => "error",
+        _ => "fatal",
+    }
+}
+
+/// 解析一行 JSON 日志（Rust 侧或 pino）；无法识别的行返回 None。
+fn parse_line(line: &str, source: &'static str) -> Option<LogEntry> {
+    let Ok(Value::Object(mut m)) = serde_json::from_str::<Value>(line) else {
+        return None;
+    };
+    let time = match m.remove("time")? {
+        Value::String(s) => parse_iso_utc(&s)?,
+        Value::Number(n) => n.as_i64()?,
+        _ => return None,
+    };
+    let level = match m.remove("level")? {
+        Value::String(s) => s.to_ascii_lowercase(),
+        Value::Number(n) => pino_level_name(n.as_u64()?).to_string(),
+        _ => return None,
+    };
+    let msg = match m.remove("msg") {
+        Some(Value::String(s)) => s,
+        Some(v) => v.to_string(),
+        None => String::new(),
+    };
+    let target = m
+        .remove("target")
+        .and_then(|v| v.as_str().map(String::from));
+    // pino 每行都带的进程信息不作为附加字段返回
+    m.remove("pid");
+    m.remove("hostname");
+    Some(LogEntry {
+        time,
+        level,
+        source,
+        target,
+        msg,
+        fields: m,
+    })
+}
+
+impl LogQuery {
+    fn wants_source(&self, source: &str) -> bool {
+        self.source.as_deref().is_none_or(|s| s == source)
+    }
+
+    fn matches(&self, e: &LogEntry) -> bool {
+        let contains = self.contains.as_deref().map(str::to_lowercase);
+        self.since.is_none_or(|t| e.time >= t)
+            && self.until.is_none_or(|t| e.time <= t)
+            && self
+                .level
+                .as_deref()
+                .is_none_or(|l| level_rank(&e.level) >= level_rank(&l.to_ascii_lowercase()))
+            && self.target.as_deref().is_none_or(|t| {
+                e.target
+                    .as_deref()
+                    .is_some_and(|et| et == t || et.starts_with(&format!("{}::", t)))
+            })
+            && contains.is_none_or(|c| e.msg.to_lowercase().contains(&c))
+    }
+}
+
+fn read_entries(path: &Path, source: &'static str, query: &LogQuery, out: &mut Vec<LogEntry>) {
+    let Ok(file) = File::open(path) else {
+        return;
+    };
+    out.extend(
+        BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| parse_line(&line, source))
+            .filter(|e| query.matches(e)),
+    );
+}
+
+/// 合并 Rust 侧日志与 core 日志，按时间升序返回最新的 `limit` 条。
+pub fn query(app: &AppHandle, query: &LogQuery) -> Result<LogQueryResult, String> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_QUERY_LIMIT)
+        .clamp(1, MAX_QUERY_LIMIT);
+    let mut entries = Vec::new();
+    if query.wants_source(SOURCE_APP) {
+        let path = log_path(app)?;
+        for n in (1..=KEEP_FILES).rev() {
+            read_entries(&rotated_path(&path, n), SOURCE_APP, query, &mut entries);
+        }
+        read_entries(&path, SOURCE_APP, query, &mut entries);
+    }
+    if query.wants_source(SOURCE_CORE) {
+        // 文件名中的日期为本地日期，起始日期放宽一天
+        let first_day = query.since.map(|t| {
+            let (y, m, d) = civil_from_days((t - DAY_MS).div_euclid(DAY_MS));
+            format!("{:04}-{:02}-{:02}", y, m, d)
+        });
+        for (day, path) in core::daily_log_files(app)? {
+            if first_day.as_deref().is_none_or(|f| day.as_str() >= f) {
+                read_entries(&path, SOURCE_CORE, query, &mut entries);
+            }
+        }
+    }
+    entries.sort_by_key(|e| e.time);
+    let truncated = entries.len() > limit;
+    if truncated {
+        entries.drain(..entries.len() - limit);
+    }
+    Ok(LogQueryResult { entries, truncated })
+}
+
+/// 查询日志（Rust 侧与 core 合并为一条时间线）。
+#[tauri::command]
+pub async fn logs_query(app: AppHandle, query: Option<LogQuery>) -> Result<LogQueryResult, String> {
+    let query = query.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || self::query(&app, &query))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 当前级别与日志文件路径，供前端展示。
+#[tauri::command]
+pub fn logs_info(app: AppHandle) -> Result<Value, String> {
+    Ok(serde_json::json!({
+        "level": level_name(),
+        "path": log_path(&app)?,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_days_round_trip() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        for days in (-800_000..800_000).step_by(7) {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days_from_civil(y, m, d), days, "{}-{}-{}", y, m, d);
+        }
+    }
+
+    #[test]
+    fn leap_years() {
+        let day_before = |y, m, d| civil_from_days(days_from_civil(y, m, d) - 1);
+        assert_eq!(day_before(2024, 3, 1), (2024, 2, 29));
+        assert_eq!(day_before(2023, 3, 1), (2023, 2, 28));
+        assert_eq!(day_before(2000, 3, 1), (2000, 2, 29));
+        assert_eq!(day_before(1900, 3, 1), (1900, 2, 28));
+        assert_eq!(day_before(2100, 3, 1), (2100, 2, 28));
+        assert_eq!(
+            parse_iso_utc("2024-02-29T12:00:00Z"),
+            Some(1_709_208_000_000)
+        );
+    }
+
+    #[test]
+    fn iso_round_trip() {
+        assert_eq!(iso_utc(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(iso_utc(1_700_000_000_123), "2023-11-14T22:13:20.123Z");
+        for ms in [0, 1, 999, 86_399_999, 1_700_000_000_123, -1, -86_400_001] {
+            assert_eq!(parse_iso_utc(&iso_utc(ms)), Some(ms), "{}", ms);
+        }
+    }
+
+    #[test]
+    fn fractional_seconds() {
+        let base = 1_700_000_000_000;
+        assert_eq!(parse_iso_utc("2023-11-14T22:13:20Z"), Some(base));
+        assert_eq!(parse_iso_utc("2023-11-14T22:13:20.1Z"), Some(base + 100));
+        assert_eq!(parse_iso_utc("2023-11-14T22:13:20.12Z"), Some(base + 120));
+        assert_eq!(
+            parse_iso_utc("2023-11-14T22:13:20.123456Z"),
+            Some(base + 123)
+        );
+        assert_eq!(parse_iso_utc("2023-11-14T22:13:20.Z"), None);
+        assert_eq!(parse_iso_utc("2023-11-14T22:13:20.1aZ"), None);
+        assert_eq!(parse_iso_utc("2023-11-14T22:13:20+08:00"), None);
+        assert_eq!(parse_iso_utc("2023-11-14 22:13:20Z"), None);
+    }
+
+    #[test]
+    fn rotate_shifts_files() {
+        let dir = std::env::temp_dir().join(format!("logging-rotate-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(LOG_FILE_NAME);
+        fs::write(&path, "current").unwrap();
+        for n in 1..=KEEP_FILES {
+            fs::write(rotated_path(&path, n), n.to_string()).unwrap();
+        }
+
+        rotate(&path).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+        assert_eq!(
+            fs::read_to_string(rotated_path(&path, 1)).unwrap(),
+            "current"
+        );
+        for n in 2..=KEEP_FILES {
+            let shifted = fs::read_to_string(rotated_path(&path, n)).unwrap();
+            assert_eq!(shifted, (n - 1).to_string());
+        }
+        assert!(!rotated_path(&path, KEEP_FILES + 1).exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,358 @@
+//! 多 profile（工作区）：每个 profile 有独立的数据库、store、备份与用户服务清单，core 以其目录作为 APP_DATA_DIR。
+//!
+//! - `default` 即 app_data 根目录（沿用单 profile 时期的数据）；其他 profile 位于 `<app_data>/profiles/<name>/`；
+//! - 启动时依次取 `--profile <name>` 参数、上次使用的 profile（`profiles/state.json`）、`default`；
+//!   `--profile` 指定的 profile 不存在时自动创建；
+//! - 切换（[profile_switch]）：停止全部服务 → 切换目录 → 执行 store / 数据库迁移 → 重新加载用户服务清单 →
+//!   重新启动 core 与 autostart 服务，完成后 emit `profile-switched`，前端据此重新加载
+//!   （前端的 store 与数据库均按当前 profile 定位，见 `db_location`）。
+//!
+//! clawbot、core 更新包与 store 加密密钥属于整个安装，仍在 app_data 根目录，各 profile 共享。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::db::{self, DB_LOCK};
+use crate::services::{supervisor, user};
+use crate::store;
+use crate::store::migrate::write_json_atomic;
+
+pub const DEFAULT_PROFILE: &str = "default";
+const PROFILES_DIR_NAME: &str = "profiles";
+const STATE_FILE_NAME: &str = "state.json";
+const MAX_NAME_LEN: usize = 32;
+pub const CLI_FLAG: &str = "--profile";
+
+/// 当前 profile；None 表示 [DEFAULT_PROFILE]。
+static ACTIVE: Mutex<Option<String>> = Mutex::new(None);
+
+/// 对应 `<app_data>/profiles/state.json`。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ProfileState {
+    last_used: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileInfo {
+    pub name: String,
+    pub data_dir: String,
+    pub active: bool,
+}
+
+/// profile 名会作为目录名使用：1–32 个字母、数字、`-` 或 `_`。
+pub fn check_name(name: &str) -> Result<(), String> {
+    let ok = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+    if ok {
+        Ok(())
+    } else {
+        Err(format!(
+            "非法的 profile 名: {}（仅限 {} 个以内的字母、数字、- 和 _）",
+            name, MAX_NAME_LEN
+        ))
+    }
+}
+
+fn app_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path().app_data_dir().map_err(|e| e.to_string())
+}
+
+/// `<app_data>/profiles`
+fn profiles_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app_data_dir(app)?.join(PROFILES_DIR_NAME))
+}
+
+/// profile 的数据目录：`default` 为 app_data 根目录，其余为 `<app_data>/profiles/<name>`。
+pub fn dir_of(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    if name == DEFAULT_PROFILE {
+        app_data_dir(app)
+    } else {
+        check_name(name)?;
+        Ok(profiles_dir(app)?.join(name))
+    }
+}
+
+pub fn exists(app: &AppHandle, name: &str) -> bool {
+    name == DEFAULT_PROFILE || dir_of(app, name).is_ok_and(|d| d.is_dir())
+}
+
+/// 当前 profile 名。
+pub fn active() -> String {
+    ACTIVE
+        .lock()
+        .ok()
+        .and_then(|a| a.clone())
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+}
+
+/// 当前 profile 的数据目录：数据库、store 与各自备份都在这里。
+pub fn data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    dir_of(app, &active())
+}
+
+fn read_state(dir: &Path) -> ProfileState {
+    fs::read_to_string(dir.join(STATE_FILE_NAME))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+/// 只切换进程内的当前 profile，不记为最近使用。
+fn select(name: &str) {
+    if let Ok(mut slot) = ACTIVE.lock() {
+        *slot = (name != DEFAULT_PROFILE).then(|| name.to_string());
+    }
+}
+
+fn set_active(app: &AppHandle, name: &str) -> Result<(), String> {
+    select(name);
+    let dir = profiles_dir(app)?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let state = ProfileState {
+        last_used: Some(name.to_string()),
+    };
+    let value = serde_json::to_value(&state).map_err(|e| e.to_string())?;
+    write_json_atomic(&dir.join(STATE_FILE_NAME), &value)
+}
+
+/// 命令行中的 `--profile <name>` 或 `--profile=<name>`。
+pub fn cli_profile() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == CLI_FLAG {
+            return args.next();
+        }
+        if let Some(name) = arg.strip_prefix(CLI_FLAG).and_then(|r| r.strip_prefix('=')) {
+            return Some(name.to_string());
+        }
+    }
+    None
+}
+
+/// Setup 阶段最先调用（早于 store / 数据库迁移）：确定当前 profile 并记为最近使用。
+pub fn init_on_setup(app: &AppHandle) {
+    let remembered = profiles_dir(app)
+        .ok()
+        .and_then(|dir| read_state(&dir).last_used)
+        .filter(|name| exists(app, name));
+    let name = match cli_profile() {
+        Some(name) => match ensure(app, &name) {
+            Ok(()) => name,
+            Err(e) => {
+                log::warn!("忽略 {} {}: {}", CLI_FLAG, name, e);
+                remembered.unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+            }
+        },
+        None => remembered.unwrap_or_else(|| DEFAULT_PROFILE.to_string()),
+    };
+    if let Err(e) = set_active(app, &name) {
+        log::warn!("记录当前 profile 失败: {}", e);
+    }
+    log::info!("当前 profile: {}", name);
+}
+
+/// 命令行子命令（见 [crate::cli]）使用：与 setup 相同的选择顺序，但 `--profile` 指定的 profile
+/// 须已存在，且不改写最近使用记录。返回选中的 profile 名。
+pub fn init_for_cli(app: &AppHandle) -> Result<String, String> {
+    let name = match cli_profile() {
+        Some(name) => {
+            check_name(&name)?;
+            if !exists(app, &name) {
+                return Err(format!("profile {} 不存在", name));
+            }
+            name
+        }
+        None => profiles_dir(app)
+            .ok()
+            .and_then(|dir| read_state(&dir).last_used)
+            .filter(|name| exists(app, name))
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_string()),
+    };
+    select(&name);
+    Ok(name)
+}
+
+/// profile 不存在时创建。
+fn ensure(app: &AppHandle, name: &str) -> Result<(), String> {
+    if exists(app, name) {
+        return Ok(());
+    }
+    create(app, name)?;
+    log::info!("已创建 profile {}", name);
+    Ok(())
+}
+
+pub fn list(app: &AppHandle) -> Result<Vec<ProfileInfo>, String> {
+    let current = active();
+    let mut names = vec![DEFAULT_PROFILE.to_string()];
+    if let Ok(entries) = fs::read_dir(profiles_dir(app)?) {
+        let mut named: Vec<String> = entries
+            .flatten()
+            .filter(|e| e.path().is_dir())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .filter(|n| n != DEFAULT_PROFILE && check_name(n).is_ok())
+            .collect();
+        named.sort();
+        names.extend(named);
+    }
+    names
+        .into_iter()
+        .map(|name| -> Result<ProfileInfo, String> {
+            Ok(ProfileInfo {
+                data_dir: dir_of(app, &name)?.to_string_lossy().to_string(),
+                active: name == current,
+                name,
+            })
+        })
+        .collect()
+}
+
+pub fn create(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    check_name(name)?;
+    if exists(app, name) {
+        return Err(format!("profile {} 已存在", name));
+    }
+    let dir = dir_of(app, name)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("创建 {} 失败: {}", dir.display(), e))?;
+    Ok(dir)
+}
+
+/// 以 `source` 的数据库与 store 文件为初始内容新建 profile；备份不复制。
+/// 数据库经在线备份复制，`source` 正在被 core 使用时同样安全。
+pub fn clone(app: &AppHandle, source: &str, name: &str) -> Result<PathBuf, String> {
+    if !exists(app, source) {
+        return Err(format!("profile {} 不存在", source));
+    }
+    let src = dir_of(app, source)?;
+    let dest = create(app, name)?;
+    let result = (|| -> Result<(), String> {
+        let db_name = crate::config::get_sqlite_db_name(app);
+        let src_db = src.join(&db_name);
+        if src_db.is_file() {
+            db::backup::copy_database(&db::open(&src_db)?, &dest.join(&db_name))?;
+        }
+        for store_path in store::backup::list_store_paths(&src) {
+            fs::copy(src.join(&store_path), dest.join(&store_path))
+                .map_err(|e| format!("复制 {} 失败: {}", store_path, e))?;
+        }
+        Ok(())
+    })();
+    if let Err(e) = result {
+        let _ = fs::remove_dir_all(&dest);
+        return Err(e);
+    }
+    Ok(dest)
+}
+
+/// 删除 profile 及其全部数据；`default` 与当前 profile 不可删除。
+pub fn delete(app: &AppHandle, name: &str) -> Result<(), String> {
+    if name == DEFAULT_PROFILE {
+        return Err("default profile 不可删除".to_string());
+    }
+    if name == active() {
+        return Err(format!("profile {} 正在使用，请先切换到其他 profile", name));
+    }
+    if !exists(app, name) {
+        return Err(format!("profile {} 不存在", name));
+    }
+    let dir = dir_of(app, name)?;
+    fs::remove_dir_all(&dir).map_err(|e| format!("删除 {} 失败: {}", dir.display(), e))
+}
+
+/// 切换到 `name`：停止全部服务后切换目录并迁移，再按启动流程拉起 core 与 autostart 服务。
+pub fn switch(app: &AppHandle, name: &str) -> Result<ProfileInfo, String> {
+    if !exists(app, name) {
+        return Err(format!("profile {} 不存在", name));
+    }
+    let _guard = DB_LOCK.lock().map_err(|e| e.to_string())?;
+    if name != active() {
+        supervisor::stop_all(app);
+        crate::core::clear_runtime(app);
+        set_active(app, name)?;
+        log::info!("已切换到 {}", name);
+        store::migrate::run_on_setup(app);
+        db::migrate::run_on_setup(app);
+        // 用户服务清单随 profile 切换
+        if let Err(e) = user::reload(app) {
+            log::error!("加载用户服务失败: {}", e);
+        }
+        crate::core::start_on_setup(app);
+        supervisor::spawn_autostart(app);
+    }
+    let info = ProfileInfo {
+        name: name.to_string(),
+        data_dir: data_dir(app)?.to_string_lossy().to_string(),
+        active: true,
+    };
+    let _ = app.emit("profile-switched", &info);
+    Ok(info)
+}
+
+/// 全部 profile（`default` 在前，其余按名称排序）。
+#[tauri::command]
+pub fn profile_list(app: AppHandle) -> Result<Vec<ProfileInfo>, String> {
+    list(&app)
+}
+
+/// 当前 profile。
+#[tauri::command]
+pub fn profile_current(app: AppHandle) -> Result<ProfileInfo, String> {
+    let name = active();
+    Ok(ProfileInfo {
+        data_dir: dir_of(&app, &name)?.to_string_lossy().to_string(),
+        name,
+        active: true,
+    })
+}
+
+/// 新建空 profile。
+#[tauri::command]
+pub fn profile_create(app: AppHandle, name: String) -> Result<ProfileInfo, String> {
+    let dir = create(&app, &name)?;
+    Ok(ProfileInfo {
+        name,
+        data_dir: dir.to_string_lossy().to_string(),
+        active: false,
+    })
+}
+
+/// 复制 `source` 的数据库与 store 为新 profile。
+#[tauri::command]
+pub async fn profile_clone(
+    app: AppHandle,
+    source: String,
+    name: String,
+) -> Result<ProfileInfo, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let dir = clone(&app, &source, &name)?;
+        Ok(ProfileInfo {
+            name,
+            data_dir: dir.to_string_lossy().to_string(),
+            active: false,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn profile_delete(app: AppHandle, name: String) -> Result<(), String> {
+    delete(&app, &name)
+}
+
+/// 切换 profile 并重启 core；完成后 emit `profile-switched`。
+#[tauri::command]
+pub async fn profile_switch(app: AppHandle, name: String) -> Result<ProfileInfo, String> {
+    tauri::async_runtime::spawn_blocking(move || switch(&app, &name))
+        .await
+        .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,151 @@
+//! 服务依赖图：拓扑排序与环检测。
+//!
+//! 图以「服务 → 其依赖」表示，由各清单的 `dependsOn` 与其他清单的 `requiredBy` 合并而来，
+//! 见 [build_graph]。
+
+use std::collections::{BTreeMap, HashSet};
+
+use super::manifest::ServiceManifest;
+
+/// 依赖图：服务名 → 依赖的服务名。
+pub type DepGraph = BTreeMap<String, Vec<String>>;
+
+/// 由清单构建依赖图：各清单的 `dependsOn`，加上其他清单以 `requiredBy` 反向声明的依赖。
+pub fn build_graph(manifests: &[&ServiceManifest]) -> DepGraph {
+    let mut graph: DepGraph = manifests
+        .iter()
+        .map(|m| (m.name.clone(), m.depends_on.clone()))
+        .collect();
+    for m in manifests {
+        for target in &m.required_by {
+            // 未注册的 requiredBy 目标忽略（如用户清单声明了 core，但 core 未启用）
+            if let Some(deps) = graph.get_mut(target) {
+                if !deps.contains(&m.name) {
+                    deps.push(m.name.clone());
+                }
+            }
+        }
+    }
+    graph
+}
+
+/// 返回 `roots` 及其全部（传递）依赖的启动顺序：依赖在前。
+/// 依赖了未注册的服务或存在循环依赖时返回错误，循环会列出完整路径（如 `a -> b -> a`）。
+pub fn start_order(graph: &DepGraph, roots: &[String]) -> Result<Vec<String>, String> {
+    let mut order = Vec::new();
+    let mut done = HashSet::new();
+    let mut path = Vec::new();
+    for root in roots {
+        if !graph.contains_key(root) {
+            return Err(format!("未知服务: {}", root));
+        }
+        visit(graph, root, &mut path, &mut done, &mut order)?;
+    }
+    Ok(order)
+}
+
+fn visit(
+    graph: &DepGraph,
+    name: &str,
+    path: &mut Vec<String>,
+    done: &mut HashSet<String>,
+    order: &mut Vec<String>,
+) -> Result<(), String> {
+    if done.contains(name) {
+        return Ok(());
+    }
+    if let Some(pos) = path.iter().position(|p| p == name) {
+        let mut cycle: Vec<&str> = path[pos..].iter().map(String::as_str).collect();
+        cycle.push(name);
+        return Err(format!("服务依赖存在循环: {}", cycle.join(" -> ")));
+    }
+    path.push(name.to_string());
+    for dep in graph.get(name).map(Vec::as_slice).unwrap_or_default() {
+        if !graph.contains_key(dep) {
+            return Err(format!("服务 {} 依赖了未注册的服务 {}", name, dep));
+        }
+        visit(graph, dep, path, done, order)?;
+    }
+    path.pop();
+    done.insert(name.to_string());
+    order.push(name.to_string());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(name: &str, depends_on: &[&str], required_by: &[&str]) -> ServiceManifest {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "command": name,
+            "dependsOn": depends_on,
+            "requiredBy": required_by,
+        }))
+        .unwrap()
+    }
+
+    fn graph(edges: &[(&str, &[&str])]) -> DepGraph {
+        edges
+            .iter()
+            .map(|(name, deps)| {
+                let deps = deps.iter().map(|d| d.to_string()).collect();
+                (name.to_string(), deps)
+            })
+            .collect()
+    }
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn dependencies_come_first() {
+        let g = graph(&[("core", &["db", "cache"]), ("cache", &["db"]), ("db", &[])]);
+        assert_eq!(
+            start_order(&g, &names(&["core"])).unwrap(),
+            names(&["db", "cache", "core"])
+        );
+        // 已排过的依赖不重复出现
+        assert_eq!(
+            start_order(&g, &names(&["cache", "core"])).unwrap(),
+            names(&["db", "cache", "core"])
+        );
+    }
+
+    #[test]
+    fn cycle_reports_full_path() {
+        let g = graph(&[("a", &["b"]), ("b", &["c"]), ("c", &["b"])]);
+        let err = start_order(&g, &names(&["a"])).unwrap_err();
+        assert_eq!(err, "服务依赖存在循环: b -> c -> b");
+
+        let g = graph(&[("a", &["a"])]);
+        let err = start_order(&g, &names(&["a"])).unwrap_err();
+        assert_eq!(err, "服务依赖存在循环: a -> a");
+    }
+
+    #[test]
+    fn missing_dependency_or_root() {
+        let g = graph(&[("core", &["db"])]);
+        let err = start_order(&g, &names(&["core"])).unwrap_err();
+        assert_eq!(err, "服务 core 依赖了未注册的服务 db");
+        let err = start_order(&g, &names(&["nope"])).unwrap_err();
+        assert_eq!(err, "未知服务: nope");
+    }
+
+    #[test]
+    fn required_by_merges_into_target() {
+        let core = manifest("core", &["db"], &[]);
+        let db = manifest("db", &[], &["core"]);
+        let proxy = manifest("proxy", &[], &["core", "missing"]);
+        let g = build_graph(&[&core, &db, &proxy]);
+        // db 已在 core 的 dependsOn 中，不重复添加；未注册的目标忽略
+        assert_eq!(g["core"], names(&["db", "proxy"]));
+        assert!(!g.contains_key("missing"));
+        assert_eq!(
+            start_order(&g, &names(&["core"])).unwrap(),
+            names(&["db", "proxy", "core"])
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! 托管服务清单：如何启动一个子进程、何时算就绪、端口如何分配、退出后是否重启。
+//!
+//! `command` / `args` / `env` / `cwd` / `readiness.healthUrl` 中可使用模板变量 `${NAME}`，
+//! 由 [template_vars] 提供：`PORT`、`APP_DATA_DIR`（当前 profile 的数据目录）、`APP_LOG_DIR`、`RESOURCE_DIR`、
+//! `SERVICE_NAME`、`PROFILE`。
+//! 未知变量原样保留。
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::profile;
+
+/// 端口分配方式。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PortStrategy {
+    /// 不需要端口
+    #[default]
+    None,
+    /// 启动时用 portpicker 选一个空闲端口
+    Pick,
+    /// 使用 `value` 指定的固定端口
+    Fixed,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct PortSpec {
+    pub strategy: PortStrategy,
+    /// `fixed` 时的端口
+    pub value: Option<u16>,
+    /// 除模板变量 `${PORT}` 外，另以该环境变量名传入端口（如 core 的 `API_PORT`）
+    pub env: Option<String>,
+}
+
+/// 就绪判定：二者都未配置时 spawn 成功即视为就绪。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct ReadinessSpec {
+    /// stdout 中出现该字符串即就绪
+    pub stdout_marker: Option<String>,
+    /// 轮询该地址直到返回 2xx
+    pub health_url: Option<String>,
+    /// 超时未就绪则判定失败并停止进程；缺省 [DEFAULT_READY_TIMEOUT_SECS]
+    pub timeout_secs: Option<u64>,
+}
+
+pub const DEFAULT_READY_TIMEOUT_SECS: u64 = 60;
+
+impl ReadinessSpec {
+    pub fn is_empty(&self) -> bool {
+        self.stdout_marker.is_none() && self.health_url.is_none()
+    }
+}
+
+/// 进程退出后的重启策略。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartPolicy {
+    #[default]
+    Never,
+    /// 退出码非 0（或被信号终止）时重启
+    OnFailure,
+    Always,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct RestartSpec {
+    pub policy: RestartPolicy,
+    /// 连续自动重启的上限，手动启动后清零
+    pub max_retries: u32,
+    /// 第 n 次重启前等待 n × backoffMs
+    pub backoff_ms: u64,
+}
+
+impl Default for RestartSpec {
+    fn default() -> Self {
+        Self {
+            policy: RestartPolicy::Never,
+            max_retries: 3,
+            backoff_ms: 1000,
+        }
+    }
+}
+
+/// 一个托管服务的完整描述。未知字段视为错误，便于发现手写清单中的拼写问题。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ServiceManifest {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// 可执行文件；`sidecar` 为 true 时是 tauri.conf.json 中 externalBin 的名字
+    pub command: String,
+    #[serde(default)]
+    pub sidecar: bool,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub readiness: ReadinessSpec,
+    #[serde(default)]
+    pub port: PortSpec,
+    #[serde(default)]
+    pub restart: RestartSpec,
+    /// 应用启动时自动启动（按依赖顺序，见 [super::supervisor::spawn_autostart]）
+    #[serde(default)]
+    pub autostart: bool,
+    /// 依赖的服务：它们全部就绪后才启动本服务
+    #[serde(default, alias = "depends_on")]
+    pub depends_on: Vec<String>,
+    /// 反向依赖：列出的服务（如 `core`）要等本服务就绪后才启动
+    #[serde(default, alias = "required_by")]
+    pub required_by: Vec<String>,
+}
+
+impl ServiceManifest {
+    /// 名称只允许字母数字与 `-_`，端口配置须自洽。
+    pub fn validate(&self) -> Result<(), String> {
+        let name_ok = !self.name.is_empty()
+            && self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+        if !name_ok {
+            return Err(format!("非法的服务名: {:?}", self.name));
+        }
+        if self.command.trim().is_empty() {
+            return Err(format!("服务 {} 缺少 command", self.name));
+        }
+        match self.port.strategy {
+            PortStrategy::Fixed if self.port.value.is_none() => {
+                return Err(format!(
+                    "服务 {} 的 port.strategy 为 fixed 但未给出 value",
+                    self.name
+                ));
+            }
+            PortStrategy::None if self.port.env.is_some() => {
+                return Err(format!("服务 {} 未分配端口却配置了 port.env", self.name));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// 模板变量表。
+pub fn template_vars(app: &AppHandle, name: &str, port: Option<u16>) -> HashMap<String, String> {
+    let mut vars = HashMap::new();
+    vars.insert("SERVICE_NAME".to_string(), name.to_string());
+    vars.insert("PROFILE".to_string(), profile::active());
+    if let Some(port) = port {
+        vars.insert("PORT".to_string(), port.to_string());
+    }
+    let dirs = [
+        ("APP_DATA_DIR", profile::data_dir(app).ok()),
+        ("APP_LOG_DIR", app.path().app_log_dir().ok()),
+        ("RESOURCE_DIR", app.path().resource_dir().ok()),
+    ];
+    for (key, dir) in dirs {
+        if let Some(dir) = dir {
+            vars.insert(key.to_string(), dir.to_string_lossy().to_string());
+        }
+    }
+    vars
+}
+
+/// 展开 `${NAME}`；未知变量保留原文。
+pub fn expand(template: &str, vars: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find('}') {
+            Some(end) => {
+                let key = &after[..end];
+                match vars.get(key) {
+                    Some(v) => out.push_str(v),
+                    None => out.push_str(&rest[start..start + 3 + end]),
+                }
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
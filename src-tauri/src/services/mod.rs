@@ -0,0 +1,75 @@
+//! 托管服务：core 侧车、Clawbot（见 [crate::clawbot::service]）等子进程统一由 [supervisor] 启动、监控与停止，清单格式见 [manifest]；
+//! 用户可在当前 profile 数据目录的 `services/` 下放置 `*.service.json` 声明额外服务，见 [user]。
+//!
+//! 事件：`service-ready`（{ name, port }）、`service-exited`（{ name, code }）、
+//! `service-failed`（{ name, reason }）。
+
+pub mod deps;
+pub mod manifest;
+pub mod supervisor;
+pub mod user;
+
+use tauri::{AppHandle, Manager};
+
+use supervisor::{LogLine, ServiceInfo, Supervisor};
+use user::{UserManifestFile, UserServices};
+
+/// [service_logs] 默认返回的行数。
+const DEFAULT_LOG_LINES: usize = 200;
+
+/// 全部已注册服务的状态（按注册顺序）。
+#[tauri::command]
+pub fn services_list(app: AppHandle) -> Vec<ServiceInfo> {
+    app.try_state::<Supervisor>()
+        .map(|s| s.list())
+        .unwrap_or_default()
+}
+
+/// 启动服务及其依赖；等待依赖就绪可能较久，因此放到阻塞线程执行。
+#[tauri::command]
+pub async fn service_start(app: AppHandle, name: String) -> Result<ServiceInfo, String> {
+    tauri::async_runtime::spawn_blocking(move || supervisor::start(&app, &name))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn service_stop(app: AppHandle, name: String) -> Result<ServiceInfo, String> {
+    supervisor::stop(&app, &name)
+}
+
+#[tauri::command]
+pub async fn service_restart(app: AppHandle, name: String) -> Result<ServiceInfo, String> {
+    tauri::async_runtime::spawn_blocking(move || supervisor::restart(&app, &name))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 返回服务最近若干行输出（stdout / stderr / 监管器自身记录）。
+#[tauri::command]
+pub fn service_logs(
+    app: AppHandle,
+    name: String,
+    lines: Option<usize>,
+) -> Result<Vec<LogLine>, String> {
+    let sup = app
+        .try_state::<Supervisor>()
+        .ok_or_else(|| "Supervisor 未注册".to_string())?;
+    sup.logs(&name, lines.unwrap_or(DEFAULT_LOG_LINES))
+}
+
+/// 最近一次扫描到的用户服务清单（含不合法文件及原因）。
+#[tauri::command]
+pub fn services_manifests(app: AppHandle) -> Result<Vec<UserManifestFile>, String> {
+    let state = app
+        .try_state::<UserServices>()
+        .ok_or_else(|| "UserServices 未注册".to_string())?;
+    let files = state.0.lock().map_err(|e| e.to_string())?;
+    Ok(files.clone())
+}
+
+/// 重新扫描当前 profile 的 `services/` 并注册新增 / 修改的清单（不会自动启动）。
+#[tauri::command]
+pub fn services_reload(app: AppHandle) -> Result<Vec<UserManifestFile>, String> {
+    user::reload(&app)
+}
@@ -0,0 +1,923 @@
+//! 托管服务的运行时：按 [ServiceManifest] 启动 / 停止子进程，跟踪就绪、退出与重启。
+//!
+//! - 进程统一经 tauri-plugin-shell 启动（sidecar 或普通命令），stdout / stderr 原样转发到终端
+//!   （`--headless` 下本进程的 stdout 留给机器可读的 JSON，子进程 stdout 改转发到 stderr），
+//!   同时保留最近 [LOG_CAPACITY] 行供 `service_logs` 查询；配置了 `log_file` hook 的服务另追加写入日志文件；
+//! - 就绪后 emit `service-ready`，退出后 emit `service-exited`，就绪超时 emit `service-failed`；
+//! - 每次 spawn 递增 `run_id`，旧进程迟到的事件（如手动 stop 后的 Terminated）据此忽略；
+//! - PID 另存到 [SERVICE_PIDS]，Ctrl+C 时由 [kill_all_by_pid] 终止；spawn 期间持有该锁，
+//!   信号处理会等 PID 登记完成后再执行；
+//! - 启动前先按依赖拓扑序启动依赖，并等依赖就绪（readiness gating）；全部停止时按逆序进行。
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_http::reqwest;
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+use tauri_plugin_shell::ShellExt;
+
+use super::deps::{self, DepGraph};
+use super::manifest::{
+    expand, template_vars, PortStrategy, RestartPolicy, ServiceManifest, DEFAULT_READY_TIMEOUT_SECS,
+};
+use crate::headless;
+use crate::util::now_millis;
+
+/// 每个服务在内存中保留的日志行数。
+const LOG_CAPACITY: usize = 1000;
+/// 日志文件超过该大小时在下次启动前轮转为 `<文件名>.1`。
+const LOG_FILE_ROTATE_BYTES: u64 = 5 * 1024 * 1024;
+/// 健康检查轮询间隔。
+const HEALTH_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// 等待依赖就绪时的检查间隔。
+const READY_WAIT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 各服务 PID 的全局副本，用于 Ctrl+C 时在信号处理里按 PID 终止（Drop 可能来不及执行）。
+static SERVICE_PIDS: OnceLock<Mutex<HashMap<String, u32>>> = OnceLock::new();
+
+fn pid_table() -> &'static Mutex<HashMap<String, u32>> {
+    SERVICE_PIDS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn forget_pid(name: &str) {
+    if let Ok(mut g) = pid_table().lock() {
+        g.remove(name);
+    }
+}
+
+/// 强制终止指定 PID（Windows 用 taskkill，其余平台 kill -9）。
+pub(crate) fn kill_pid(pid: u32) {
+    #[cfg(windows)]
+    {
+        let _ = std::process::Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/F"])
+            .status();
+    }
+    #[cfg(not(windows))]
+    {
+        let _ = std::process::Command::new("kill")
+            .args(["-9", &pid.to_string()])
+            .status();
+    }
+}
+
+/// 进程是否仍存在（Windows 用 tasklist，其余平台 kill -0）。
+pub(crate) fn pid_alive(pid: u32) -> bool {
+    #[cfg(windows)]
+    {
+        std::process::Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/NH", "/FO", "CSV"])
+            .output()
+            .is_ok_and(|o| String::from_utf8_lossy(&o.stdout).contains(&format!("\"{}\"", pid)))
+    }
+    #[cfg(not(windows))]
+    {
+        std::process::Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .stderr(std::process::Stdio::null())
+            .status()
+            .is_ok_and(|s| s.success())
+    }
+}
+
+/// 按 PID 终止全部托管服务（供 Ctrl+C 等信号处理使用）。
+pub fn kill_all_by_pid() {
+    let pids: Vec<u32> = pid_table()
+        .lock()
+        .map(|mut g| g.drain().map(|(_, pid)| pid).collect())
+        .unwrap_or_default();
+    for pid in pids {
+        kill_pid(pid);
+    }
+}
+
+/// Rust 侧针对个别服务的回调（如 core 就绪后 emit `core-ready`），清单本身只含可序列化数据。
+#[derive(Clone, Copy, Default)]
+pub struct ServiceHooks {
+    /// spawn 成功后调用：(app, pid, port)
+    pub on_started: Option<fn(&AppHandle, u32, Option<u16>)>,
+    /// 就绪后调用：(app, port)
+    pub on_ready: Option<fn(&AppHandle, Option<u16>)>,
+    /// 启动失败（含依赖失败）时调用：(app, 失败类型, 原因)
+    pub on_failed: Option<fn(&AppHandle, FailureKind, &str)>,
+    /// 每次 spawn 前调用，返回值追加到清单 env 之后（如 core 需要其他服务的实时端口）
+    pub extra_env: Option<fn(&AppHandle) -> Vec<(String, String)>>,
+    /// 每次 spawn 前调用，返回 Err 时不启动并判定为启动失败（如 core 的完整性校验）
+    pub before_spawn: Option<fn(&AppHandle, &ServiceManifest) -> Result<(), String>>,
+    /// 每次 spawn 前调用，返回 Some 时 stdout / stderr 另追加写入该文件（如 Clawbot 的 clawbot.log）
+    pub log_file: Option<fn(&AppHandle) -> Option<PathBuf>>,
+}
+
+/// 启动失败的类型，供 `on_failed` 区分是服务自身的问题还是环境 / 依赖的问题。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    /// 依赖图有环或缺失，或依赖未能就绪
+    Dependency,
+    /// `before_spawn` 拒绝启动（如完整性校验未通过）
+    Rejected,
+    /// 进程未能拉起（入口 / 侧车不存在、无法分配端口等）
+    Spawn,
+    /// 进程在就绪前退出且不再重启
+    ExitedBeforeReady,
+    /// 超时仍未就绪
+    ReadyTimeout,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ServiceStatus {
+    Stopped,
+    Starting,
+    Ready,
+    /// 正常退出（退出码 0）
+    Exited,
+    /// 异常退出、启动失败或就绪超时
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogLine {
+    /// Unix 毫秒
+    pub at: u64,
+    /// "stdout" / "stderr" / "supervisor"
+    pub stream: &'static str,
+    pub line: String,
+}
+
+/// `services_list` 等命令返回的服务快照。
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceInfo {
+    pub name: String,
+    pub status: ServiceStatus,
+    pub pid: Option<u32>,
+    pub port: Option<u16>,
+    pub uptime_secs: Option<u64>,
+    pub exit_code: Option<i32>,
+    pub restarts: u32,
+    pub last_error: Option<String>,
+}
+
+struct ServiceEntry {
+    manifest: ServiceManifest,
+    hooks: ServiceHooks,
+    child: Option<CommandChild>,
+    pid: Option<u32>,
+    port: Option<u16>,
+    status: ServiceStatus,
+    started_at: Option<Instant>,
+    exit_code: Option<i32>,
+    last_error: Option<String>,
+    /// 自上次手动启动以来的自动重启次数
+    restarts: u32,
+    run_id: u64,
+    logs: VecDeque<LogLine>,
+}
+
+impl ServiceEntry {
+    fn new(manifest: ServiceManifest, hooks: ServiceHooks) -> Self {
+        Self {
+            manifest,
+            hooks,
+            child: None,
+            pid: None,
+            port: None,
+            status: ServiceStatus::Stopped,
+            started_at: None,
+            exit_code: None,
+            last_error: None,
+            restarts: 0,
+            run_id: 0,
+            logs: VecDeque::new(),
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        self.child.is_some()
+    }
+
+    fn info(&self) -> ServiceInfo {
+        ServiceInfo {
+            name: self.manifest.name.clone(),
+            status: self.status,
+            pid: self.pid,
+            port: self.port,
+            uptime_secs: self.started_at.map(|t| t.elapsed().as_secs()),
+            exit_code: self.exit_code,
+            restarts: self.restarts,
+            last_error: self.last_error.clone(),
+        }
+    }
+
+    fn push_log(&mut self, stream: &'static str, line: String) {
+        if self.logs.len() >= LOG_CAPACITY {
+            self.logs.pop_front();
+        }
+        self.logs.push_back(LogLine {
+            at: now_millis(),
+            stream,
+            line,
+        });
+    }
+}
+
+/// 托管服务表（按注册顺序），作为 Tauri 托管状态；应用退出时（Drop）kill 全部子进程。
+#[derive(Default)]
+pub struct Supervisor {
+    entries: Mutex<Vec<ServiceEntry>>,
+}
+
+impl Drop for Supervisor {
+    fn drop(&mut self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            for entry in entries.iter_mut() {
+                if let Some(child) = entry.child.take() {
+                    if let Err(e) = child.kill() {
+                        log::warn!("关闭 {} 失败: {}", entry.manifest.name, e);
+                    } else {
+                        log::info!("已关闭 {}", entry.manifest.name);
+                    }
+                }
+                forget_pid(&entry.manifest.name);
+            }
+        }
+    }
+}
+
+impl Supervisor {
+    /// 注册（或替换未运行的同名）服务。
+    pub fn register(&self, manifest: ServiceManifest, hooks: ServiceHooks) -> Result<(), String> {
+        manifest.validate()?;
+        let mut entries = self.entries.lock().map_err(|e| e.to_string())?;
+        match entries
+            .iter_mut()
+            .find(|e| e.manifest.name == manifest.name)
+        {
+            Some(entry) if entry.is_running() => {
+                Err(format!("服务 {} 正在运行，无法替换", manifest.name))
+            }
+            Some(entry) => {
+                entry.manifest = manifest;
+                entry.hooks = hooks;
+                Ok(())
+            }
+            None => {
+                entries.push(ServiceEntry::new(manifest, hooks));
+                Ok(())
+            }
+        }
+    }
+
+    /// 移除未运行的服务；运行中的保持不变并返回错误，不存在时忽略。
+    pub fn unregister(&self, name: &str) -> Result<(), String> {
+        let mut entries = self.entries.lock().map_err(|e| e.to_string())?;
+        match entries.iter().position(|e| e.manifest.name == name) {
+            Some(i) if entries[i].is_running() => Err(format!("服务 {} 正在运行，无法移除", name)),
+            Some(i) => {
+                entries.remove(i);
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    pub fn list(&self) -> Vec<ServiceInfo> {
+        self.entries
+            .lock()
+            .map(|entries| entries.iter().map(ServiceEntry::info).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn info(&self, name: &str) -> Result<ServiceInfo, String> {
+        self.with_entry(name, |e| e.info())
+    }
+
+    /// 最近 `lines` 行日志（旧的在前）。
+    pub fn logs(&self, name: &str, lines: usize) -> Result<Vec<LogLine>, String> {
+        self.with_entry(name, |e| {
+            let skip = e.logs.len().saturating_sub(lines);
+            e.logs.iter().skip(skip).cloned().collect()
+        })
+    }
+
+    /// 依赖图：合并各清单的 `dependsOn` 与其他清单的 `requiredBy`。
+    pub fn dependency_map(&self) -> DepGraph {
+        let Ok(entries) = self.entries.lock() else {
+            return DepGraph::new();
+        };
+        let manifests: Vec<&ServiceManifest> = entries.iter().map(|e| &e.manifest).collect();
+        deps::build_graph(&manifests)
+    }
+
+    /// `roots` 及其依赖的启动顺序（依赖在前）。
+    pub fn start_order(&self, roots: &[String]) -> Result<Vec<String>, String> {
+        deps::start_order(&self.dependency_map(), roots)
+    }
+
+    fn autostart_names(&self) -> Vec<String> {
+        self.entries
+            .lock()
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter(|e| e.manifest.autostart)
+                    .map(|e| e.manifest.name.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn with_entry<T>(
+        &self,
+        name: &str,
+        f: impl FnOnce(&mut ServiceEntry) -> T,
+    ) -> Result<T, String> {
+        let mut entries = self.entries.lock().map_err(|e| e.to_string())?;
+        let entry = entries
+            .iter_mut()
+            .find(|e| e.manifest.name == name)
+            .ok_or_else(|| format!("未知服务: {}", name))?;
+        Ok(f(entry))
+    }
+}
+
+fn supervisor(app: &AppHandle) -> Result<tauri::State<'_, Supervisor>, String> {
+    app.try_state::<Supervisor>()
+        .ok_or_else(|| "Supervisor 未注册".to_string())
+}
+
+// ---------------------------------------------------------------------------
+// 启动 / 停止
+// ---------------------------------------------------------------------------
+
+/// 手动启动服务（清零自动重启计数）：先按拓扑序启动依赖，每个服务启动前等其依赖就绪。
+/// 会阻塞到依赖就绪为止，不要在主线程调用。已在运行时直接返回当前状态。
+pub fn start(app: &AppHandle, name: &str) -> Result<ServiceInfo, String> {
+    try_start(app, name).map_err(|(_, e)| e)
+}
+
+/// 同 [start]，失败时一并返回失败类型（依赖的失败一律为 [FailureKind::Dependency]）。
+pub fn try_start(app: &AppHandle, name: &str) -> Result<ServiceInfo, (FailureKind, String)> {
+    let sup = supervisor(app).map_err(|e| (FailureKind::Spawn, e))?;
+    let graph = sup.dependency_map();
+    let order =
+        deps::start_order(&graph, &[name.to_string()]).map_err(|e| (FailureKind::Dependency, e))?;
+    for svc in &order {
+        for dep in graph.get(svc).map(Vec::as_slice).unwrap_or_default() {
+            wait_ready(app, dep).map_err(|e| (FailureKind::Dependency, e))?;
+        }
+        spawn(app, svc, true).map_err(|(kind, e)| {
+            if svc == name {
+                (kind, e)
+            } else {
+                (FailureKind::Dependency, e)
+            }
+        })?;
+    }
+    sup.info(name).map_err(|e| (FailureKind::Spawn, e))
+}
+
+/// 阻塞直到服务就绪；服务不再处于启动中（失败、退出、被停止）时返回错误。
+/// 就绪超时由 [spawn_ready_timeout] 负责，因此这里不会无限等待。
+fn wait_ready(app: &AppHandle, name: &str) -> Result<(), String> {
+    loop {
+        let status = supervisor(app)?.with_entry(name, |e| e.status)?;
+        match status {
+            ServiceStatus::Ready => return Ok(()),
+            ServiceStatus::Starting => std::thread::sleep(READY_WAIT_INTERVAL),
+            other => return Err(format!("依赖 {} 未就绪（{:?}）", name, other)),
+        }
+    }
+}
+
+/// 标记启动失败并调用 `on_failed` hook。
+fn fail(app: &AppHandle, name: &str, kind: FailureKind, reason: String) {
+    log::error!("{} 启动失败: {}", name, reason);
+    let Ok(sup) = supervisor(app) else {
+        return;
+    };
+    let hooks = sup.with_entry(name, |e| {
+        if !e.is_running() {
+            e.status = ServiceStatus::Failed;
+        }
+        e.last_error = Some(reason.clone());
+        e.push_log("supervisor", reason.clone());
+        e.hooks
+    });
+    if let Ok(ServiceHooks {
+        on_failed: Some(f), ..
+    }) = hooks
+    {
+        f(app, kind, &reason);
+    }
+}
+
+/// Setup 阶段调用：在后台线程按依赖顺序启动全部 `autostart` 服务。
+/// 依赖启动失败的服务不再启动；启动顺序按每个服务单独计算，依赖图有环或缺失时只有受影响的服务标记失败。
+pub fn spawn_autostart(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let Ok(sup) = supervisor(&app) else {
+            return;
+        };
+        let roots = sup.autostart_names();
+        let graph = sup.dependency_map();
+        // 依赖在前的各段顺序依次合并、去重后仍是依赖在前
+        let mut order: Vec<String> = Vec::new();
+        for root in &roots {
+            match deps::start_order(&graph, std::slice::from_ref(root)) {
+                Ok(o) => {
+                    for name in o {
+                        if !order.contains(&name) {
+                            order.push(name);
+                        }
+                    }
+                }
+                Err(e) => fail(&app, root, FailureKind::Dependency, e),
+            }
+        }
+        let mut failed: HashSet<String> = HashSet::new();
+        for name in order {
+            let deps = graph.get(&name).cloned().unwrap_or_default();
+            let result = match deps.iter().find(|d| failed.contains(*d)) {
+                Some(bad) => Err((FailureKind::Dependency, format!("依赖 {} 启动失败", bad))),
+                None => deps
+                    .iter()
+                    .try_for_each(|d| wait_ready(&app, d))
+                    .map_err(|e| (FailureKind::Dependency, e))
+                    .and_then(|_| spawn(&app, &name, true).map(|_| ())),
+            };
+            if let Err((kind, e)) = result {
+                fail(&app, &name, kind, e);
+                failed.insert(name);
+            }
+        }
+    });
+}
+
+/// 停止服务；未运行时直接返回当前状态。
+pub fn stop(app: &AppHandle, name: &str) -> Result<ServiceInfo, String> {
+    let sup = supervisor(app)?;
+    let (child, info) = sup.with_entry(name, |e| {
+        // 递增 run_id：让旧进程随后的 Terminated 事件被忽略，不触发重启
+        e.run_id += 1;
+        let child = e.child.take();
+        if child.is_some() {
+            e.status = ServiceStatus::Stopped;
+            e.pid = None;
+            e.started_at = None;
+            e.push_log("supervisor", "已停止".to_string());
+        }
+        (child, e.info())
+    })?;
+    forget_pid(name);
+    if let Some(child) = child {
+        child
+            .kill()
+            .map_err(|e| format!("停止 {} 失败: {}", name, e))?;
+        log::info!("已停止 {}", name);
+    }
+    Ok(info)
+}
+
+pub fn restart(app: &AppHandle, name: &str) -> Result<ServiceInfo, String> {
+    stop(app, name)?;
+    start(app, name)
+}
+
+/// 停止全部服务：按启动顺序的逆序（依赖方先停）；依赖图无效时按注册顺序的逆序。
+pub fn stop_all(app: &AppHandle) {
+    let Ok(sup) = supervisor(app) else {
+        return;
+    };
+    let names: Vec<String> = sup.list().into_iter().map(|s| s.name).collect();
+    let order = sup.start_order(&names).unwrap_or(names);
+    for name in order.iter().rev() {
+        if let Err(e) = stop(app, name) {
+            log::warn!("停止 {} 失败: {}", name, e);
+        }
+    }
+}
+
+/// 只启动服务本身（不处理依赖）。
+fn spawn(app: &AppHandle, name: &str, manual: bool) -> Result<ServiceInfo, (FailureKind, String)> {
+    let spawn_err = |e: String| (FailureKind::Spawn, e);
+    let sup = supervisor(app).map_err(spawn_err)?;
+    // hook 可能读取其他服务状态或做耗时校验，须在持有表锁之前调用
+    let (running, hooks, manifest) = sup
+        .with_entry(name, |e| (e.is_running(), e.hooks, e.manifest.clone()))
+        .map_err(spawn_err)?;
+    if let (false, Some(f)) = (running, hooks.before_spawn) {
+        if let Err(err) = f(app, &manifest) {
+            sup.with_entry(name, |e| {
+                e.status = ServiceStatus::Failed;
+                e.last_error = Some(err.clone());
+                e.push_log("supervisor", err.clone());
+            })
+            .map_err(spawn_err)?;
+            return Err((FailureKind::Rejected, err));
+        }
+    }
+    let extra_env = match hooks.extra_env {
+        Some(f) => f(app),
+        None => Vec::new(),
+    };
+    let log_path = hooks.log_file.and_then(|f| f(app));
+    let mut entries = sup.entries.lock().map_err(|e| spawn_err(e.to_string()))?;
+    let entry = entries
+        .iter_mut()
+        .find(|e| e.manifest.name == name)
+        .ok_or_else(|| spawn_err(format!("未知服务: {}", name)))?;
+    if entry.is_running() {
+        return Ok(entry.info());
+    }
+    if manual {
+        entry.restarts = 0;
+    }
+
+    let spawned = match spawn_entry(app, entry, extra_env, log_path.as_deref()) {
+        Ok(v) => v,
+        Err(e) => {
+            entry.status = ServiceStatus::Failed;
+            entry.last_error = Some(e.clone());
+            entry.push_log("supervisor", e.clone());
+            return Err(spawn_err(e));
+        }
+    };
+    drop(entries);
+
+    log::info!(
+        "{} 已启动 | PID: {} | 端口 {:?}",
+        name,
+        spawned.pid,
+        spawned.port
+    );
+    if let Some(f) = spawned.hooks.on_started {
+        f(app, spawned.pid, spawned.port);
+    }
+    if spawned.ready_now {
+        mark_ready(app, name, spawned.run_id);
+    }
+    sup.info(name).map_err(spawn_err)
+}
+
+struct Spawned {
+    pid: u32,
+    port: Option<u16>,
+    run_id: u64,
+    hooks: ServiceHooks,
+    /// 清单未配置就绪判定，spawn 成功即就绪
+    ready_now: bool,
+}
+
+/// 以追加方式打开服务日志文件（必要时先轮转），写入一行启动标记。
+fn open_log_file(path: &Path, banner: &str) -> Result<File, String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    if fs::metadata(path).is_ok_and(|m| m.len() > LOG_FILE_ROTATE_BYTES) {
+        let rotated = PathBuf::from(format!("{}.1", path.display()));
+        let _ = fs::rename(path, rotated);
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("打开 {} 失败: {}", path.display(), e))?;
+    let _ = writeln!(file, "===== {} =====", banner);
+    Ok(file)
+}
+
+/// 按清单 spawn 进程并挂上事件循环。
+fn spawn_entry(
+    app: &AppHandle,
+    entry: &mut ServiceEntry,
+    extra_env: Vec<(String, String)>,
+    log_path: Option<&Path>,
+) -> Result<Spawned, String> {
+    let m = entry.manifest.clone();
+    let port = match m.port.strategy {
+        PortStrategy::None => None,
+        PortStrategy::Fixed => m.port.value,
+        PortStrategy::Pick => Some(
+            portpicker::pick_unused_port().ok_or_else(|| format!("无法为 {} 分配端口", m.name))?,
+        ),
+    };
+    let vars = template_vars(app, &m.name, port);
+
+    let mut cmd = if m.sidecar {
+        app.shell()
+            .sidecar(&m.command)
+            .map_err(|e| format!("{} 侧车未找到: {}", m.command, e))?
+    } else {
+        app.shell().command(expand(&m.command, &vars))
+    };
+    cmd = cmd.args(m.args.iter().map(|a| expand(a, &vars)));
+    let mut env: Vec<(String, String)> = m
+        .env
+        .iter()
+        .map(|(k, v)| (k.clone(), expand(v, &vars)))
+        .collect();
+    env.extend(extra_env);
+    if let (Some(key), Some(port)) = (&m.port.env, port) {
+        env.push((key.clone(), port.to_string()));
+    }
+    cmd = cmd.envs(env);
+    if let Some(cwd) = &m.cwd {
+        cmd = cmd.current_dir(expand(cwd, &vars));
+    }
+
+    // 持锁 spawn：Ctrl+C 处理会等 PID 登记后再 kill
+    let mut pids = pid_table().lock().map_err(|e| e.to_string())?;
+    let (rx, child) = cmd
+        .spawn()
+        .map_err(|e| format!("{} 启动失败: {}", m.name, e))?;
+    let pid = child.pid();
+    pids.insert(m.name.clone(), pid);
+    drop(pids);
+
+    entry.run_id += 1;
+    entry.child = Some(child);
+    entry.pid = Some(pid);
+    entry.port = port;
+    entry.status = ServiceStatus::Starting;
+    entry.started_at = Some(Instant::now());
+    entry.exit_code = None;
+    entry.last_error = None;
+    entry.push_log(
+        "supervisor",
+        format!("已启动 | PID {} | 端口 {:?}", pid, port),
+    );
+
+    let run_id = entry.run_id;
+    // 日志文件打不开不影响服务运行，仍可经 service_logs 查看
+    let log_file = log_path.and_then(|path| {
+        let banner = format!("{} | PID {} | 端口 {:?}", m.name, pid, port);
+        open_log_file(path, &banner)
+            .inspect_err(|e| log::warn!("{} 日志文件不可用: {}", m.name, e))
+            .ok()
+    });
+    spawn_event_loop(app, &m, run_id, rx, log_file);
+    if !m.readiness.is_empty() {
+        let timeout = m
+            .readiness
+            .timeout_secs
+            .unwrap_or(DEFAULT_READY_TIMEOUT_SECS);
+        spawn_ready_timeout(app, &m.name, run_id, Duration::from_secs(timeout));
+        if let Some(url) = &m.readiness.health_url {
+            spawn_health_poll(app, &m.name, run_id, expand(url, &vars));
+        }
+    }
+    Ok(Spawned {
+        pid,
+        port,
+        run_id,
+        hooks: entry.hooks,
+        ready_now: m.readiness.is_empty(),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// 事件处理
+// ---------------------------------------------------------------------------
+
+/// 在后台消费 stdout/stderr（否则缓冲区满会导致子进程阻塞），检测就绪标记与退出。
+fn spawn_event_loop(
+    app: &AppHandle,
+    m: &ServiceManifest,
+    run_id: u64,
+    mut rx: tauri::async_runtime::Receiver<CommandEvent>,
+    mut log_file: Option<File>,
+) {
+    let app = app.clone();
+    let name = m.name.clone();
+    let marker = m.readiness.stdout_marker.clone();
+    let stdout_to_stderr = headless::is_enabled();
+    tauri::async_runtime::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            match event {
+                CommandEvent::Stdout(line) => {
+                    if stdout_to_stderr {
+                        let _ = std::io::stderr().write_all(&line);
+                        let _ = std::io::stderr().flush();
+                    } else {
+                        let _ = std::io::stdout().write_all(&line);
+                        let _ = std::io::stdout().flush();
+                    }
+                    if let Some(f) = log_file.as_mut() {
+                        let _ = f.write_all(&line);
+                    }
+                    let text = String::from_utf8_lossy(&line).trim_end().to_string();
+                    let is_ready = marker.as_deref().is_some_and(|m| text.contains(m));
+                    record_log(&app, &name, run_id, "stdout", text);
+                    if is_ready {
+                        mark_ready(&app, &name, run_id);
+                    }
+                }
+                CommandEvent::Stderr(line) => {
+                    let _ = std::io::stderr().write_all(&line);
+                    let _ = std::io::stderr().flush();
+                    if let Some(f) = log_file.as_mut() {
+                        let _ = f.write_all(&line);
+                    }
+                    let text = String::from_utf8_lossy(&line).trim_end().to_string();
+                    record_log(&app, &name, run_id, "stderr", text);
+                }
+                CommandEvent::Error(e) => record_log(&app, &name, run_id, "supervisor", e),
+                CommandEvent::Terminated(payload) => on_exit(&app, &name, run_id, payload.code),
+                _ => {}
+            }
+        }
+    });
+}
+
+fn record_log(app: &AppHandle, name: &str, run_id: u64, stream: &'static str, line: String) {
+    if let Ok(sup) = supervisor(app) {
+        let _ = sup.with_entry(name, |e| {
+            if e.run_id == run_id {
+                e.push_log(stream, line);
+            }
+        });
+    }
+}
+
+/// 标记就绪并通知前端与 hook；非当前 run 或已不在 Starting 时忽略。
+fn mark_ready(app: &AppHandle, name: &str, run_id: u64) {
+    let Ok(sup) = supervisor(app) else {
+        return;
+    };
+    let ready = sup.with_entry(name, |e| {
+        if e.run_id != run_id || e.status != ServiceStatus::Starting {
+            return None;
+        }
+        e.status = ServiceStatus::Ready;
+        e.push_log("supervisor", "已就绪".to_string());
+        Some((e.port, e.hooks))
+    });
+    let Ok(Some((port, hooks))) = ready else {
+        return;
+    };
+    let _ = app.emit(
+        "service-ready",
+        serde_json::json!({ "name": name, "port": port }),
+    );
+    if let Some(f) = hooks.on_ready {
+        f(app, port);
+    }
+}
+
+/// 进程退出：记录退出码，按重启策略决定是否在退避后重新拉起。
+/// 就绪前退出且不再重启时视为启动失败，调用 `on_failed` hook。
+fn on_exit(app: &AppHandle, name: &str, run_id: u64, code: Option<i32>) {
+    let Ok(sup) = supervisor(app) else {
+        return;
+    };
+    let decision = sup.with_entry(name, |e| {
+        if e.run_id != run_id {
+            return None;
+        }
+        let before_ready = e.status == ServiceStatus::Starting;
+        e.child = None;
+        e.pid = None;
+        e.started_at = None;
+        e.exit_code = code;
+        e.status = if code == Some(0) {
+            ServiceStatus::Exited
+        } else {
+            ServiceStatus::Failed
+        };
+        e.push_log("supervisor", format!("进程退出，退出码 {:?}", code));
+        let spec = &e.manifest.restart;
+        let wants = match spec.policy {
+            RestartPolicy::Never => false,
+            RestartPolicy::OnFailure => code != Some(0),
+            RestartPolicy::Always => true,
+        };
+        let restart_after = if wants && e.restarts < spec.max_retries {
+            e.restarts += 1;
+            Some(Duration::from_millis(
+                spec.backoff_ms * u64::from(e.restarts),
+            ))
+        } else {
+            None
+        };
+        let on_failed = if before_ready && restart_after.is_none() {
+            e.hooks.on_failed
+        } else {
+            None
+        };
+        Some((restart_after, on_failed))
+    });
+    let Ok(Some((restart_after, on_failed))) = decision else {
+        return;
+    };
+    forget_pid(name);
+    log::info!("{} 已退出，退出码 {:?}", name, code);
+    let _ = app.emit(
+        "service-exited",
+        serde_json::json!({ "name": name, "code": code }),
+    );
+    if let Some(f) = on_failed {
+        f(
+            app,
+            FailureKind::ExitedBeforeReady,
+            &format!("就绪前退出，退出码 {:?}", code),
+        );
+    }
+
+    if let Some(delay) = restart_after {
+        let app = app.clone();
+        let name = name.to_string();
+        std::thread::spawn(move || {
+            std::thread::sleep(delay);
+            // 等待期间被手动停止 / 启动过则不再重启
+            let still_current = supervisor(&app)
+                .and_then(|sup| sup.with_entry(&name, |e| e.run_id == run_id && !e.is_running()))
+                .unwrap_or(false);
+            if still_current {
+                log::info!("按重启策略重新启动 {}", name);
+                if let Err((_, e)) = spawn(&app, &name, false) {
+                    log::error!("重启 {} 失败: {}", name, e);
+                }
+            }
+        });
+    }
+}
+
+/// 超时仍未就绪：判定失败并停止进程。
+fn spawn_ready_timeout(app: &AppHandle, name: &str, run_id: u64, timeout: Duration) {
+    let app = app.clone();
+    let name = name.to_string();
+    std::thread::spawn(move || {
+        std::thread::sleep(timeout);
+        let Ok(sup) = supervisor(&app) else {
+            return;
+        };
+        let child = sup.with_entry(&name, |e| {
+            if e.run_id != run_id || e.status != ServiceStatus::Starting {
+                return None;
+            }
+            let msg = format!("{} 秒内未就绪", timeout.as_secs());
+            e.run_id += 1;
+            e.status = ServiceStatus::Failed;
+            e.pid = None;
+            e.started_at = None;
+            e.last_error = Some(msg.clone());
+            e.push_log("supervisor", msg.clone());
+            Some((e.child.take(), e.hooks.on_failed, msg))
+        });
+        if let Ok(Some((child, on_failed, msg))) = child {
+            forget_pid(&name);
+            if let Some(child) = child {
+                let _ = child.kill();
+            }
+            log::error!("{} 就绪超时，已停止", name);
+            let _ = app.emit(
+                "service-failed",
+                serde_json::json!({ "name": name, "reason": "ready-timeout" }),
+            );
+            if let Some(f) = on_failed {
+                f(&app, FailureKind::ReadyTimeout, &msg);
+            }
+        }
+    });
+}
+
+/// 轮询健康检查地址，直到返回 2xx、进程换代或不再处于 Starting。
+fn spawn_health_poll(app: &AppHandle, name: &str, run_id: u64, url: String) {
+    let app = app.clone();
+    let name = name.to_string();
+    tauri::async_runtime::spawn(async move {
+        let client = reqwest::Client::new();
+        loop {
+            let pending = supervisor(&app)
+                .and_then(|sup| {
+                    sup.with_entry(&name, |e| {
+                        e.run_id == run_id && e.status == ServiceStatus::Starting
+                    })
+                })
+                .unwrap_or(false);
+            if !pending {
+                return;
+            }
+            let ok = client
+                .get(&url)
+                .timeout(Duration::from_secs(2))
+                .send()
+                .await
+                .is_ok_and(|r| r.status().is_success());
+            if ok {
+                mark_ready(&app, &name, run_id);
+                return;
+            }
+            tokio::time::sleep(HEALTH_POLL_INTERVAL).await;
+        }
+    });
+}
@@ -0,0 +1,244 @@
+//! 用户自定义服务：`<profile 数据目录>/services/*.service.json`，每个文件一个 [ServiceManifest]。
+//!
+//! - 清单随 profile 隔离（见 [crate::profile]）：切换 profile 时停止全部服务，移除上一 profile 的清单
+//!   并加载新 profile 的清单；克隆 profile 会一并复制清单；
+//! - 旧版本放在 `<app_config_dir>/services` 的清单在首次加载时移入 `default` profile；
+//! - Setup 阶段扫描并校验，合法的注册进 [Supervisor]，`autostart: true` 的随后与 core 一起按依赖顺序启动；
+//! - `dependsOn` / `requiredBy` 引用了未注册服务或构成循环时，该清单标记为不合法并从 [Supervisor] 移除，
+//!   不影响 core 与其他服务的启动；
+//! - 不合法的文件连同原因保留在 [UserServices] 中，供前端展示；
+//! - 已知端口（运行中分配的端口或 `fixed` 端口）以 `SERVICE_<NAME>_PORT` 注入 core 环境，
+//!   见 [port_env]；`<NAME>` 为服务名大写、`-` 换成 `_`。要确保 core 拿到 `pick` 端口，
+//!   清单中应写 `"requiredBy": ["core"]`。
+//!
+//! 用户服务只能使用普通命令，不能使用 sidecar，也不能占用内置服务名（如 `core`）。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use super::manifest::{PortStrategy, ServiceManifest};
+use super::supervisor::{ServiceHooks, Supervisor};
+use crate::core::CORE_SERVICE_NAME;
+use crate::profile;
+
+const SERVICES_DIR_NAME: &str = "services";
+const MANIFEST_SUFFIX: &str = ".service.json";
+
+/// 一个清单文件的加载结果。
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserManifestFile {
+    pub file: PathBuf,
+    /// 解析成功时的清单
+    pub manifest: Option<ServiceManifest>,
+    /// 解析或校验失败的原因
+    pub error: Option<String>,
+}
+
+/// 最近一次扫描的结果，作为 Tauri 托管状态。
+#[derive(Default)]
+pub struct UserServices(pub Mutex<Vec<UserManifestFile>>);
+
+/// `<profile 数据目录>/services`
+pub fn services_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(profile::data_dir(app)?.join(SERVICES_DIR_NAME))
+}
+
+/// 旧版本的 `<app_config_dir>/services` 移入 `default` profile（目标已存在时不动）。
+fn migrate_legacy_dir(app: &AppHandle) {
+    let Ok(legacy) = app
+        .path()
+        .app_config_dir()
+        .map(|d| d.join(SERVICES_DIR_NAME))
+    else {
+        return;
+    };
+    let Ok(target) =
+        profile::dir_of(app, profile::DEFAULT_PROFILE).map(|d| d.join(SERVICES_DIR_NAME))
+    else {
+        return;
+    };
+    if legacy == target || !legacy.is_dir() || target.exists() {
+        return;
+    }
+    if let Some(parent) = target.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    match fs::rename(&legacy, &target) {
+        Ok(()) => log::info!("已将 {} 移至 {}", legacy.display(), target.display()),
+        Err(e) => log::warn!("迁移旧的服务清单目录失败: {}", e),
+    }
+}
+
+fn load_file(path: &Path) -> Result<ServiceManifest, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("读取失败: {}", e))?;
+    let manifest: ServiceManifest =
+        serde_json::from_str(&text).map_err(|e| format!("格式错误: {}", e))?;
+    manifest.validate()?;
+    if manifest.name == CORE_SERVICE_NAME {
+        return Err(format!("服务名 {} 为内置服务保留", manifest.name));
+    }
+    if manifest.sidecar {
+        return Err("用户服务不能使用 sidecar".to_string());
+    }
+    Ok(manifest)
+}
+
+/// 扫描目录下全部 `*.service.json`（按文件名排序），同名服务只保留第一个。
+pub fn scan(dir: &Path) -> Vec<UserManifestFile> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.is_file()
+                && p.file_name()
+                    .is_some_and(|n| n.to_string_lossy().ends_with(MANIFEST_SUFFIX))
+        })
+        .collect();
+    files.sort();
+
+    let mut out: Vec<UserManifestFile> = Vec::new();
+    for file in files {
+        let result = load_file(&file).and_then(|m| {
+            let taken = out
+                .iter()
+                .filter_map(|f| f.manifest.as_ref())
+                .any(|other| other.name == m.name);
+            if taken {
+                Err(format!("服务名 {} 与其他清单重复", m.name))
+            } else {
+                Ok(m)
+            }
+        });
+        let (manifest, error) = match result {
+            Ok(m) => (Some(m), None),
+            Err(e) => (None, Some(e)),
+        };
+        out.push(UserManifestFile {
+            file,
+            manifest,
+            error,
+        });
+    }
+    out
+}
+
+/// 重新扫描并把合法清单注册进 [Supervisor]（正在运行的同名服务保持不变），返回扫描结果。
+/// 上次加载过、这次已不存在（如切换了 profile）的清单从 [Supervisor] 移除。
+pub fn reload(app: &AppHandle) -> Result<Vec<UserManifestFile>, String> {
+    let mut files = scan(&services_dir(app)?);
+    let previous: Vec<String> = app
+        .try_state::<UserServices>()
+        .and_then(|s| {
+            s.0.lock().ok().map(|files| {
+                files
+                    .iter()
+                    .filter_map(|f| f.manifest.as_ref().map(|m| m.name.clone()))
+                    .collect()
+            })
+        })
+        .unwrap_or_default();
+    if let Some(sup) = app.try_state::<Supervisor>() {
+        for name in previous.iter().filter(|name| {
+            !files
+                .iter()
+                .any(|f| f.manifest.as_ref().is_some_and(|m| &m.name == *name))
+        }) {
+            if let Err(e) = sup.unregister(name) {
+                log::warn!("{}", e);
+            }
+        }
+        for f in files.iter_mut() {
+            let Some(m) = f.manifest.clone() else {
+                continue;
+            };
+            if let Err(e) = sup.register(m, ServiceHooks::default()) {
+                f.error = Some(e);
+            }
+        }
+        // 移除一个清单可能让依赖它的清单也不合法，反复检查直到没有新的错误
+        loop {
+            let mut removed = false;
+            for f in files.iter_mut().filter(|f| f.error.is_none()) {
+                let Some(name) = f.manifest.as_ref().map(|m| m.name.clone()) else {
+                    continue;
+                };
+                if let Err(e) = sup.start_order(std::slice::from_ref(&name)) {
+                    if let Err(running) = sup.unregister(&name) {
+                        log::warn!("{}", running);
+                    }
+                    f.error = Some(e);
+                    removed = true;
+                }
+            }
+            if !removed {
+                break;
+            }
+        }
+    }
+    for f in files.iter().filter(|f| f.error.is_some()) {
+        log::warn!(
+            "跳过 {}: {}",
+            f.file.display(),
+            f.error.as_deref().unwrap_or_default()
+        );
+    }
+    if let Some(state) = app.try_state::<UserServices>() {
+        *state.0.lock().map_err(|e| e.to_string())? = files.clone();
+    }
+    Ok(files)
+}
+
+/// Setup 阶段调用：加载并注册清单；autostart 由 [super::supervisor::spawn_autostart] 统一处理。
+pub fn load_on_setup(app: &AppHandle) {
+    migrate_legacy_dir(app);
+    if let Err(e) = reload(app) {
+        log::error!("加载用户服务失败: {}", e);
+    }
+}
+
+/// `SERVICE_<NAME>_PORT` 中的 `<NAME>`。
+fn env_name(name: &str) -> String {
+    name.to_ascii_uppercase().replace('-', "_")
+}
+
+/// 用户服务的端口环境变量：运行中的取实际端口，否则取 `fixed` 端口；都没有则不注入。
+pub fn port_env(app: &AppHandle) -> Vec<(String, String)> {
+    let Some(state) = app.try_state::<UserServices>() else {
+        return Vec::new();
+    };
+    let Ok(files) = state.0.lock() else {
+        return Vec::new();
+    };
+    let running = app
+        .try_state::<Supervisor>()
+        .map(|s| s.list())
+        .unwrap_or_default();
+    files
+        .iter()
+        .filter(|f| f.error.is_none())
+        .filter_map(|f| f.manifest.as_ref())
+        .filter_map(|m| {
+            let live = running
+                .iter()
+                .find(|s| s.name == m.name && s.pid.is_some())
+                .and_then(|s| s.port);
+            let fixed = (m.port.strategy == PortStrategy::Fixed)
+                .then_some(m.port.value)
+                .flatten();
+            live.or(fixed).map(|port| {
+                (
+                    format!("SERVICE_{}_PORT", env_name(&m.name)),
+                    port.to_string(),
+                )
+            })
+        })
+        .collect()
+}
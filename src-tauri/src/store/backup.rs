@@ -0,0 +1,229 @@
+//! Store 导出 / 导入与滚动快照。
+//!
+//! - 导出：单个或全部 store 文件打包为一个 JSON bundle（附应用版本、导出时间）。
+//! - 导入：bundle 按 `merge`（逐 key 覆盖）或 `replace`（整文件替换）写回，导入前先快照。
+//! - 快照：写入前若距上次快照超过 [SNAPSHOT_MIN_INTERVAL_SECS]，复制一份到 `<profile 数据目录>/backups/`，
+//!   每个 store 只保留最近 [SNAPSHOT_KEEP] 份，可通过 [store_restore_snapshot] 恢复。
+//!   改写 store 文件的入口都会快照：`store_write`（按间隔节流；前端与 CLI 的写入都经由它，
+//!   前端不直接使用 store 插件）、`store_encrypt_migrate`、导入与恢复（强制）。
+//!   TTL 到期清理（见 [super::ttl]）只删除已过期的 key，不快照。
+//!
+//! 加密信封按原样导出，只能导入到同一安装（同一 store.key）。
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+
+use super::migrate::BACKUP_DIR_NAME;
+use super::resolve_path;
+use crate::profile;
+use crate::util::now_secs;
+
+/// bundle 格式标识，导入时校验。
+const BUNDLE_FORMAT: &str = "langchainapp-store-bundle";
+const BUNDLE_FORMAT_VERSION: u64 = 1;
+
+const SNAPSHOT_SUFFIX: &str = ".snapshot";
+const SNAPSHOT_KEEP: usize = 10;
+const SNAPSHOT_MIN_INTERVAL_SECS: u64 = 5 * 60;
+
+/// 导出的 bundle：`stores` 为 store path → 全部 key/value。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoreBundle {
+    pub format: String,
+    pub format_version: u64,
+    pub app_version: String,
+    pub exported_at: u64,
+    pub stores: Map<String, JsonValue>,
+}
+
+/// 导入方式。
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// 逐 key 覆盖，bundle 中没有的 key 保留。
+    Merge,
+    /// 清空后整体写入。
+    Replace,
+}
+
+/// 一份快照的描述，`id` 即文件名，供恢复时传回。
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotInfo {
+    pub id: String,
+    pub store_path: String,
+    pub created_at: u64,
+    pub size: u64,
+}
+
+/// 数据目录顶层的全部 store 文件（*.json）。
+pub(crate) fn list_store_paths(app_data: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(app_data) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<String> = entries
+        .flatten()
+        .filter(|e| e.path().is_file())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .filter(|n| n.ends_with(".json"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// 解析快照文件名 `<store path>.<ts>.snapshot`。
+fn parse_snapshot_name(name: &str) -> Option<(String, u64)> {
+    let stem = name.strip_suffix(SNAPSHOT_SUFFIX)?;
+    let (store_path, ts) = stem.rsplit_once('.')?;
+    Some((store_path.to_string(), ts.parse().ok()?))
+}
+
+fn list_snapshots_in(backup_dir: &Path) -> Vec<SnapshotInfo> {
+    let Ok(entries) = fs::read_dir(backup_dir) else {
+        return Vec::new();
+    };
+    let mut list: Vec<SnapshotInfo> = entries
+        .flatten()
+        .filter_map(|e| {
+            let id = e.file_name().to_string_lossy().to_string();
+            let (store_path, created_at) = parse_snapshot_name(&id)?;
+            let size = e.metadata().map(|m| m.len()).unwrap_or(0);
+            Some(SnapshotInfo {
+                id,
+                store_path,
+                created_at,
+                size,
+            })
+        })
+        .collect();
+    list.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    list
+}
+
+/// 为 store 文件拍一份快照；`force` 为 false 时距上次快照不足间隔则跳过。超出保留数量的旧快照被删除。
+pub fn snapshot_store_file(app_data: &Path, store_path: &str, force: bool) -> Result<(), String> {
+    let src = app_data.join(store_path);
+    // 子目录中的 store 不做快照，避免快照文件名里出现路径分隔符
+    if store_path.contains(['/', '\\']) || !src.is_file() {
+        return Ok(());
+    }
+    let backup_dir = app_data.join(BACKUP_DIR_NAME);
+    let now = now_secs();
+    let existing: Vec<SnapshotInfo> = list_snapshots_in(&backup_dir)
+        .into_iter()
+        .filter(|s| s.store_path == store_path)
+        .collect();
+    if !force
+        && existing
+            .first()
+            .is_some_and(|s| now.saturating_sub(s.created_at) < SNAPSHOT_MIN_INTERVAL_SECS)
+    {
+        return Ok(());
+    }
+
+    fs::create_dir_all(&backup_dir).map_err(|e| e.to_string())?;
+    let dst = backup_dir.join(format!("{}.{}{}", store_path, now, SNAPSHOT_SUFFIX));
+    fs::copy(&src, &dst).map_err(|e| format!("创建快照失败: {}", e))?;
+
+    // existing 按时间倒序，新快照之外再保留 SNAPSHOT_KEEP - 1 份
+    for old in existing.iter().skip(SNAPSHOT_KEEP.saturating_sub(1)) {
+        let _ = fs::remove_file(backup_dir.join(&old.id));
+    }
+    Ok(())
+}
+
+/// 用一组 key/value 覆盖（merge）或替换（replace）store 并落盘，返回写入的 key 数量。
+fn apply_entries(
+    app: &AppHandle,
+    store_path: &str,
+    entries: &Map<String, JsonValue>,
+    mode: ImportMode,
+) -> Result<usize, String> {
+    let store = app.store(resolve_path(app, store_path)?).map_err(|e| e.to_string())?;
+    if let ImportMode::Replace = mode {
+        store.clear();
+    }
+    for (k, v) in entries {
+        store.set(k.clone(), v.clone());
+    }
+    store.save().map_err(|e| e.to_string())?;
+    Ok(entries.len())
+}
+
+/// 导出 store 为 bundle；`path` 为空时导出当前 profile 下全部 store 文件。
+#[tauri::command]
+pub fn store_export(app: AppHandle, path: Option<String>) -> Result<StoreBundle, String> {
+    let paths = match path {
+        Some(p) => vec![p],
+        None => list_store_paths(&profile::data_dir(&app)?),
+    };
+    let mut stores = Map::new();
+    for p in paths {
+        let store = app.store(resolve_path(&app, &p)?).map_err(|e| e.to_string())?;
+        let entries: Map<String, JsonValue> = store.entries().into_iter().collect();
+        stores.insert(p, JsonValue::Object(entries));
+    }
+    Ok(StoreBundle {
+        format: BUNDLE_FORMAT.to_string(),
+        format_version: BUNDLE_FORMAT_VERSION,
+        app_version: app.package_info().version.to_string(),
+        exported_at: now_secs(),
+        stores,
+    })
+}
+
+/// 导入 bundle，返回写入的 key 总数。每个 store 导入前强制快照一次。
+#[tauri::command]
+pub fn store_import(app: AppHandle, bundle: StoreBundle, mode: ImportMode) -> Result<usize, String> {
+    if bundle.format != BUNDLE_FORMAT || bundle.format_version > BUNDLE_FORMAT_VERSION {
+        return Err(format!(
+            "不支持的 bundle 格式: {} v{}",
+            bundle.format, bundle.format_version
+        ));
+    }
+    let app_data = profile::data_dir(&app)?;
+    let mut total = 0;
+    for (store_path, entries) in &bundle.stores {
+        let JsonValue::Object(entries) = entries else {
+            return Err(format!("bundle 中 {} 不是对象", store_path));
+        };
+        if store_path.contains(['/', '\\']) || store_path.starts_with('.') {
+            return Err(format!("非法的 store path: {}", store_path));
+        }
+        snapshot_store_file(&app_data, store_path, true)?;
+        total += apply_entries(&app, store_path, entries, mode)?;
+    }
+    Ok(total)
+}
+
+/// 列出全部快照（新的在前）。
+#[tauri::command]
+pub fn store_list_snapshots(app: AppHandle) -> Result<Vec<SnapshotInfo>, String> {
+    Ok(list_snapshots_in(&profile::data_dir(&app)?.join(BACKUP_DIR_NAME)))
+}
+
+/// 用指定快照整体替换对应 store；恢复前会为当前内容再拍一份快照，便于反悔。
+#[tauri::command]
+pub fn store_restore_snapshot(app: AppHandle, id: String) -> Result<(), String> {
+    let (store_path, _) =
+        parse_snapshot_name(&id).ok_or_else(|| format!("无效的快照 id: {}", id))?;
+    if id.contains(['/', '\\']) {
+        return Err(format!("无效的快照 id: {}", id));
+    }
+    let app_data = profile::data_dir(&app)?;
+    let text = fs::read_to_string(app_data.join(BACKUP_DIR_NAME).join(&id))
+        .map_err(|e| format!("读取快照失败: {}", e))?;
+    let entries = match serde_json::from_str::<JsonValue>(&text) {
+        Ok(JsonValue::Object(m)) => m,
+        _ => return Err("快照内容不是 JSON 对象".to_string()),
+    };
+    snapshot_store_file(&app_data, &store_path, true)?;
+    apply_entries(&app, &store_path, &entries, ImportMode::Replace)?;
+    Ok(())
+}
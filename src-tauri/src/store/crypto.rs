@@ -0,0 +1,125 @@
+//! Store 加密模式：按 value 粒度做 XChaCha20-Poly1305 加密，密文以信封 JSON 存回 store 文件。
+//!
+//! - 密钥材料：`<APP_DATA_DIR>/store.key`，首次使用时随机生成 32 字节，每个安装独立。
+//! - 派生：HKDF-SHA256(密钥材料, info = store path)，不同 store 文件使用不同密钥。
+//! - 信封：`{"$enc":"xchacha20poly1305","nonce":"<b64>","data":"<b64>"}`，AAD 为 key 名，防止密文被挪到其他 key 下。
+//!
+//! 信封本身仍是合法 JSON，因此 store 文件依旧可被 tauri-plugin-store 正常加载。
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use base64::engine::general_purpose::STANDARD as B64;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use hkdf::Hkdf;
+use serde_json::{Map, Value as JsonValue};
+use sha2::Sha256;
+
+/// 密钥材料文件名，位于 app_data 目录。
+pub const KEY_FILE_NAME: &str = "store.key";
+
+const ENVELOPE_TAG: &str = "$enc";
+const ALGORITHM: &str = "xchacha20poly1305";
+const KDF_SALT: &[u8] = b"langchainapp/store/v1";
+const KEY_LEN: usize = 32;
+
+/// 读取密钥材料；不存在时生成并落盘（Unix 下权限 0600）。
+pub fn load_or_create_key_material(app_data: &Path) -> Result<[u8; KEY_LEN], String> {
+    let path = app_data.join(KEY_FILE_NAME);
+    if let Ok(bytes) = fs::read(&path) {
+        return bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| format!("密钥文件长度不正确: {}", path.display()));
+    }
+
+    let mut material = [0u8; KEY_LEN];
+    OsRng.fill_bytes(&mut material);
+    fs::create_dir_all(app_data).map_err(|e| e.to_string())?;
+    let mut opts = fs::OpenOptions::new();
+    opts.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        opts.mode(0o600);
+    }
+    let mut f = opts
+        .open(&path)
+        .map_err(|e| format!("创建密钥文件失败 {}: {}", path.display(), e))?;
+    f.write_all(&material).map_err(|e| e.to_string())?;
+    f.sync_all().map_err(|e| e.to_string())?;
+    Ok(material)
+}
+
+/// 按 store path 派生 AEAD 密钥。
+pub fn derive_key(material: &[u8; KEY_LEN], store_path: &str) -> Key {
+    let hk = Hkdf::<Sha256>::new(Some(KDF_SALT), material);
+    let mut okm = [0u8; KEY_LEN];
+    hk.expand(store_path.as_bytes(), &mut okm)
+        .expect("32 字节在 HKDF-SHA256 输出上限内");
+    Key::from(okm)
+}
+
+/// 是否为加密信封。
+pub fn is_envelope(value: &JsonValue) -> bool {
+    value
+        .get(ENVELOPE_TAG)
+        .and_then(JsonValue::as_str)
+        .is_some_and(|s| s == ALGORITHM)
+}
+
+/// 加密一个 JSON 值为信封。
+pub fn encrypt_value(key: &Key, store_key: &str, value: &JsonValue) -> Result<JsonValue, String> {
+    let plain = serde_json::to_vec(value).map_err(|e| e.to_string())?;
+    let cipher = XChaCha20Poly1305::new(key);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let data = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: &plain,
+                aad: store_key.as_bytes(),
+            },
+        )
+        .map_err(|_| "加密失败".to_string())?;
+
+    let mut m = Map::new();
+    m.insert(ENVELOPE_TAG.into(), JsonValue::String(ALGORITHM.into()));
+    m.insert("nonce".into(), JsonValue::String(B64.encode(nonce)));
+    m.insert("data".into(), JsonValue::String(B64.encode(data)));
+    Ok(JsonValue::Object(m))
+}
+
+/// 解密信封；非信封的值原样返回（兼容迁移前的明文）。
+pub fn decrypt_value(key: &Key, store_key: &str, value: JsonValue) -> Result<JsonValue, String> {
+    if !is_envelope(&value) {
+        return Ok(value);
+    }
+    let field = |name: &str| -> Result<Vec<u8>, String> {
+        let s = value
+            .get(name)
+            .and_then(JsonValue::as_str)
+            .ok_or_else(|| format!("加密信封缺少字段 {}", name))?;
+        B64.decode(s).map_err(|e| e.to_string())
+    };
+    let nonce = field("nonce")?;
+    if nonce.len() != 24 {
+        return Err("加密信封 nonce 长度不正确".to_string());
+    }
+    let data = field("data")?;
+    let cipher = XChaCha20Poly1305::new(key);
+    let plain = cipher
+        .decrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: &data,
+                aad: store_key.as_bytes(),
+            },
+        )
+        .map_err(|_| format!("解密失败（密钥不匹配或数据被篡改）: {}", store_key))?;
+    serde_json::from_slice(&plain).map_err(|e| e.to_string())
+}
@@ -0,0 +1,152 @@
+//! Store 文件版本迁移：每个 store 文件带保留 key `__schema_version`，启动时按 [MIGRATIONS] 顺序升级。
+//!
+//! - 在 setup 阶段、任何 `app.store()` 加载之前直接改写文件，前端 `whenTauriStoreReady` 读到的已是新结构。
+//! - 迁移前先把原文件备份到 `<profile 数据目录>/backups/`，写入走临时文件 + rename，避免半写。
+//! - 加密信封先用 store 的派生密钥解密，迁移后再重新加密，步骤内看到的总是明文；缺少密钥时不迁移。
+//! - 新增迁移：在 [MIGRATIONS] 末尾追加一项，version 递增。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chacha20poly1305::Key;
+use serde_json::{Map, Number, Value as JsonValue};
+use tauri::AppHandle;
+
+use super::crypto;
+use crate::config;
+use crate::profile;
+use crate::util::now_secs;
+
+/// 记录 store 结构版本的保留 key。
+pub const VERSION_KEY: &str = "__schema_version";
+
+/// 迁移备份目录名（位于 app_data 下）。
+pub const BACKUP_DIR_NAME: &str = "backups";
+
+/// 一步迁移：把数据从 `version - 1` 升级到 `version`。
+pub struct Migration {
+    pub version: u64,
+    pub description: &'static str,
+    pub apply: fn(&mut Map<String, JsonValue>),
+}
+
+/// 按 version 升序排列的全部迁移步骤。
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    description: "ai_config 补齐 defaultModelId（与 src/tauriStore/ai.ts 默认一致）",
+    apply: |data| {
+        if let Some(JsonValue::Object(cfg)) = data.get_mut("ai_config") {
+            cfg.entry("defaultModelId")
+                .or_insert_with(|| JsonValue::String("deepseek".into()));
+        }
+    },
+}];
+
+/// 当前代码期望的最新版本。
+pub fn latest_version() -> u64 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+/// 以临时文件 + rename 的方式写入 JSON，避免进程中断留下半个文件。
+pub(crate) fn write_json_atomic(path: &Path, value: &JsonValue) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, text).map_err(|e| format!("写入 {} 失败: {}", tmp.display(), e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("替换 {} 失败: {}", path.display(), e))
+}
+
+/// 对单个 store 文件执行迁移，返回迁移后的版本；文件不存在时视为无需迁移。
+/// `key` 为该 store 的派生密钥，文件中有加密信封而 `key` 为 None 时返回错误且不改动文件。
+pub fn migrate_store_file(
+    store_file: &Path,
+    backup_dir: &Path,
+    key: Option<&Key>,
+) -> Result<u64, String> {
+    let text = match fs::read_to_string(store_file) {
+        Ok(t) => t,
+        Err(_) => return Ok(latest_version()),
+    };
+    let mut data = match serde_json::from_str::<JsonValue>(&text) {
+        Ok(JsonValue::Object(m)) => m,
+        _ => return Err(format!("store 文件不是 JSON 对象: {}", store_file.display())),
+    };
+    let current = data.get(VERSION_KEY).and_then(JsonValue::as_u64).unwrap_or(0);
+    let latest = latest_version();
+    if current > latest {
+        return Err(format!(
+            "store 版本 {} 高于当前程序支持的 {}，请升级应用: {}",
+            current,
+            latest,
+            store_file.display()
+        ));
+    }
+    if current == latest {
+        return Ok(current);
+    }
+
+    let sealed: Vec<String> = data
+        .iter()
+        .filter(|(_, v)| crypto::is_envelope(v))
+        .map(|(k, _)| k.clone())
+        .collect();
+    if !sealed.is_empty() {
+        let k = key.ok_or_else(|| {
+            format!("store 含加密数据但密钥文件不存在，跳过迁移: {}", store_file.display())
+        })?;
+        for name in &sealed {
+            if let Some(value) = data.get_mut(name) {
+                *value = crypto::decrypt_value(k, name, value.take())?;
+            }
+        }
+    }
+
+    fs::create_dir_all(backup_dir).map_err(|e| e.to_string())?;
+    let file_name = store_file
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "store.json".into());
+    let backup = backup_dir.join(format!("{}.v{}.{}.bak", file_name, current, now_secs()));
+    fs::copy(store_file, &backup).map_err(|e| format!("备份 store 失败: {}", e))?;
+
+    for step in MIGRATIONS.iter().filter(|m| m.version > current) {
+        (step.apply)(&mut data);
+        data.insert(VERSION_KEY.into(), JsonValue::Number(Number::from(step.version)));
+        log::info!(
+            "迁移 {} -> v{}: {}",
+            store_file.display(),
+            step.version,
+            step.description
+        );
+    }
+    // 原本加密的 key 迁移后重新加密（sealed 非空时 key 必然存在）
+    if let Some(k) = key {
+        for name in &sealed {
+            if let Some(value) = data.get_mut(name) {
+                *value = crypto::encrypt_value(k, name, value)?;
+            }
+        }
+    }
+    write_json_atomic(store_file, &JsonValue::Object(data))?;
+    Ok(latest)
+}
+
+/// 解析配置中的 store 文件路径（当前 profile 数据目录下）。
+pub fn store_file_path(app: &AppHandle) -> Option<PathBuf> {
+    let data_dir = profile::data_dir(app).ok()?;
+    Some(data_dir.join(config::get_store_name(app)))
+}
+
+/// Setup 阶段调用：迁移配置中的 store 文件。失败时仅打印，保留原文件与备份，应用继续运行。
+pub fn run_on_setup(app: &AppHandle) {
+    let (Some(store_file), Ok(data_dir)) = (store_file_path(app), profile::data_dir(app)) else {
+        return;
+    };
+    // 未生成过密钥时 store 中不会有信封，无需创建
+    let key = super::derive_store_key(app, &config::get_store_name(app), false)
+        .ok()
+        .flatten();
+    let backup_dir = data_dir.join(BACKUP_DIR_NAME);
+    if let Err(e) = migrate_store_file(&store_file, &backup_dir, key.as_ref()) {
+        log::error!("迁移失败: {}", e);
+    }
+}
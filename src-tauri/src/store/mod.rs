@@ -0,0 +1,233 @@
+//! Tauri Store 的 invoke 封装：一次调用完成 load + get/set + save，供前端统一使用。
+//!
+//! settings.json 中 `store_encrypt` 为 true 时进入加密模式：写入时按 value 加密，读取时透明解密，
+//! 见 [crypto]。已有明文 store 可通过 [store_encrypt_migrate] 一次性转换。
+//! 加密覆盖 store 中除保留 key 外的全部 key（目前为前端写入的 `config`、`ai_config`）：
+//! 前端只经 [store_read] / [store_write] / [store_entries] 读写，不直接使用 store 插件；
+//! core 直接读 store 文件，由 [core_store_key] 经环境变量 `STORE_KEY` 取得该文件的派生密钥自行解密。
+//!
+//! 写入时可带 `ttl_secs` 设置过期，见 [ttl]。以 `__` 开头的 key 为本模块保留，不加密也不参与迁移。
+//!
+//! 注册了 JSON Schema 的 key 在写入前校验，见 [schema]；store 文件结构升级见 [migrate]；
+//! 导出 / 导入与滚动快照见 [backup]。
+//!
+//! store path 相对当前 profile 的数据目录解析（见 [resolve_path]），切换 profile 即切换一套 store。
+
+pub mod backup;
+mod crypto;
+pub mod migrate;
+pub mod schema;
+pub mod ttl;
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+
+use crate::config;
+use crate::profile;
+
+/// store 命令的错误。普通错误仍序列化为字符串；schema 校验失败序列化为带 `kind` 的对象，
+/// 列出每个失败位置的 JSON Pointer，前端可据此定位字段。
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum StoreError {
+    Message(String),
+    SchemaInvalid {
+        kind: &'static str,
+        path: String,
+        key: String,
+        violations: Vec<schema::SchemaViolation>,
+    },
+}
+
+impl From<String> for StoreError {
+    fn from(e: String) -> Self {
+        StoreError::Message(e)
+    }
+}
+
+/// 本模块内部使用的保留 key（如 [ttl::EXPIRY_KEY]）。
+fn is_reserved_key(key: &str) -> bool {
+    key.starts_with("__")
+}
+
+/// 把前端传入的 store path 解析到当前 profile 的数据目录下；加密密钥仍按原 path 派生。
+pub(crate) fn resolve_path(app: &AppHandle, path: &str) -> Result<PathBuf, String> {
+    Ok(profile::data_dir(app)?.join(path))
+}
+
+/// 指定 store 中的 key（不含保留 key），按名称排序；供 CLI 使用。
+pub fn keys(app: &AppHandle, path: &str) -> Result<Vec<String>, String> {
+    let store = app
+        .store(resolve_path(app, path)?)
+        .map_err(|e| e.to_string())?;
+    let mut keys: Vec<String> = store
+        .keys()
+        .into_iter()
+        .filter(|k| !is_reserved_key(k))
+        .collect();
+    keys.sort();
+    Ok(keys)
+}
+
+/// 加密模式下取当前 store path 的派生密钥；未开启加密时返回 None。
+fn store_key_if_encrypted(
+    app: &AppHandle<tauri::Wry>,
+    path: &str,
+) -> Result<Option<chacha20poly1305::Key>, String> {
+    if !config::get_store_encrypt(app) {
+        return Ok(None);
+    }
+    derive_store_key(app, path, true)
+}
+
+/// 读取密钥材料并派生 `path` 的密钥；`create` 为 false 且密钥文件不存在时返回 None。
+fn derive_store_key(
+    app: &AppHandle,
+    path: &str,
+    create: bool,
+) -> Result<Option<chacha20poly1305::Key>, String> {
+    let app_data = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    if !create && !app_data.join(crypto::KEY_FILE_NAME).exists() {
+        return Ok(None);
+    }
+    let material = crypto::load_or_create_key_material(&app_data)?;
+    Ok(Some(crypto::derive_key(&material, path)))
+}
+
+/// 解密信封；非信封的值原样返回。
+fn open_value(
+    app: &AppHandle,
+    path: &str,
+    key: &str,
+    value: JsonValue,
+) -> Result<JsonValue, String> {
+    if !crypto::is_envelope(&value) {
+        return Ok(value);
+    }
+    let k = derive_store_key(app, path, false)?
+        .ok_or_else(|| "密钥文件不存在，无法解密".to_string())?;
+    crypto::decrypt_value(&k, key, value)
+}
+
+/// 供 core 解密 store 文件：配置中 store 的派生密钥（base64）。未开启加密且从未生成密钥时返回 None。
+pub fn core_store_key(app: &AppHandle) -> Option<String> {
+    use base64::Engine;
+    let store_name = config::get_store_name(app);
+    let k = derive_store_key(app, &store_name, config::get_store_encrypt(app))
+        .ok()
+        .flatten()?;
+    Some(base64::engine::general_purpose::STANDARD.encode(k))
+}
+
+/// 从 Tauri Store 读取指定 path 下 key 的值，一次 invoke 完成 load + get。
+#[tauri::command]
+pub fn store_read(
+    app: AppHandle<tauri::Wry>,
+    path: String,
+    key: String,
+) -> Result<Option<JsonValue>, String> {
+    let store = app
+        .store(resolve_path(&app, &path)?)
+        .map_err(|e| e.to_string())?;
+    if ttl::purge_if_expired(&store, &key) {
+        store.save().map_err(|e| e.to_string())?;
+        return Ok(None);
+    }
+    // 加密信封一律解密，即便之后关闭了加密模式也能读回旧数据
+    store
+        .get(key.as_str())
+        .map(|v| open_value(&app, &path, &key, v))
+        .transpose()
+}
+
+/// 指定 path 下全部 key 与（解密后的）值，不含保留 key 与已过期的 key，按 key 排序。
+#[tauri::command]
+pub fn store_entries(
+    app: AppHandle<tauri::Wry>,
+    path: String,
+) -> Result<Vec<(String, JsonValue)>, String> {
+    let store = app
+        .store(resolve_path(&app, &path)?)
+        .map_err(|e| e.to_string())?;
+    ttl::purge_expired(&store)?;
+    let mut entries = store
+        .entries()
+        .into_iter()
+        .filter(|(k, _)| !is_reserved_key(k))
+        .map(|(k, v)| open_value(&app, &path, &k, v).map(|v| (k, v)))
+        .collect::<Result<Vec<_>, String>>()?;
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(entries)
+}
+
+/// 向 Tauri Store 写入指定 path 下 key 的值并落盘，一次 invoke 完成 load + set + save。
+/// `ttl_secs` 为 Some 时该 key 在对应秒数后过期；为 None 时清除已有过期设置。
+#[tauri::command]
+pub fn store_write(
+    app: AppHandle<tauri::Wry>,
+    path: String,
+    key: String,
+    value: JsonValue,
+    ttl_secs: Option<u64>,
+) -> Result<(), StoreError> {
+    if is_reserved_key(&key) {
+        return Err(format!("key {} 为保留 key，不可写入", key).into());
+    }
+    if let Some(schemas) = app.try_state::<schema::StoreSchemas>() {
+        if let Err(violations) = schemas.validate(&path, &key, &value) {
+            return Err(StoreError::SchemaInvalid {
+                kind: "schema_invalid",
+                path,
+                key,
+                violations,
+            });
+        }
+    }
+    let store = app
+        .store(resolve_path(&app, &path)?)
+        .map_err(|e| e.to_string())?;
+    let value = match store_key_if_encrypted(&app, &path)? {
+        Some(k) => crypto::encrypt_value(&k, &key, &value)?,
+        None => value,
+    };
+    if let Ok(data_dir) = profile::data_dir(&app) {
+        if let Err(e) = backup::snapshot_store_file(&data_dir, &path, false) {
+            log::warn!("写入前快照失败: {}", e);
+        }
+    }
+    ttl::set_expiry(&store, &key, ttl_secs);
+    store.set(key, value);
+    store.save().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// 将指定 path 的明文 store 全部转换为加密信封并落盘，返回被转换的 key 数量。
+/// 需先在 settings.json 中开启 `store_encrypt`；已加密的 key 跳过，可重复执行。
+#[tauri::command]
+pub fn store_encrypt_migrate(app: AppHandle<tauri::Wry>, path: String) -> Result<usize, String> {
+    let k = store_key_if_encrypted(&app, &path)?
+        .ok_or_else(|| "未开启 store_encrypt，拒绝迁移".to_string())?;
+    let store = app
+        .store(resolve_path(&app, &path)?)
+        .map_err(|e| e.to_string())?;
+    let pending: Vec<(String, JsonValue)> = store
+        .entries()
+        .into_iter()
+        .filter(|(key, value)| !is_reserved_key(key) && !crypto::is_envelope(value))
+        .collect();
+    if pending.is_empty() {
+        return Ok(0);
+    }
+    // 整体改写前强制快照：store.key 丢失或迁移结果有误时仍可恢复（快照中为明文）
+    backup::snapshot_store_file(&profile::data_dir(&app)?, &path, true)?;
+    for (key, value) in &pending {
+        let sealed = crypto::encrypt_value(&k, key, value)?;
+        store.set(key.clone(), sealed);
+    }
+    store.save().map_err(|e| e.to_string())?;
+    Ok(pending.len())
+}
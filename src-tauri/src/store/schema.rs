@@ -0,0 +1,121 @@
+//! Store key 的 JSON Schema 校验：按「store path + key」注册 schema，写入前校验，避免 bug 写入畸形对象。
+//!
+//! schema 从资源目录 `config/store-schemas/` 加载，布局为 `<store path>/<key>.schema.json`，例如：
+//!
+//! ```text
+//! config/store-schemas/
+//! └── store.json/
+//!     └── ai_config.schema.json
+//! ```
+//!
+//! 校验时按 store 文件名（path 的最后一段）查找，`./store.json`、带目录或绝对路径的写法都对应 `store.json/`。
+//! 未注册 schema 的 key 不做校验。
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use jsonschema::Validator;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+
+const SCHEMAS_RESOURCE_DIR: &str = "config/store-schemas";
+const SCHEMA_FILE_SUFFIX: &str = ".schema.json";
+
+/// 单条校验失败：出错位置（JSON Pointer）与说明。
+#[derive(Debug, Serialize)]
+pub struct SchemaViolation {
+    pub pointer: String,
+    pub message: String,
+}
+
+/// 已编译的 schema 注册表，setup 时加载后由 Tauri 托管。
+#[derive(Default)]
+pub struct StoreSchemas {
+    validators: HashMap<(String, String), Validator>,
+}
+
+impl StoreSchemas {
+    /// 从资源目录加载全部 schema；单个文件无效时跳过并打印原因，不影响其余 schema。
+    pub fn load(app: &AppHandle) -> Self {
+        let mut this = Self::default();
+        let root = match app
+            .path()
+            .resolve(SCHEMAS_RESOURCE_DIR, BaseDirectory::Resource)
+        {
+            Ok(p) if p.is_dir() => p,
+            _ => return this,
+        };
+        let Ok(store_dirs) = std::fs::read_dir(&root) else {
+            return this;
+        };
+        for store_dir in store_dirs.flatten() {
+            if !store_dir.path().is_dir() {
+                continue;
+            }
+            let store_path = store_dir.file_name().to_string_lossy().to_string();
+            let Ok(files) = std::fs::read_dir(store_dir.path()) else {
+                continue;
+            };
+            for file in files.flatten() {
+                let file_name = file.file_name().to_string_lossy().to_string();
+                let Some(key) = file_name.strip_suffix(SCHEMA_FILE_SUFFIX) else {
+                    continue;
+                };
+                match compile(&file.path()) {
+                    Ok(v) => this.register(&store_path, key, v),
+                    Err(e) => log::warn!("跳过无效 schema {}: {}", file.path().display(), e),
+                }
+            }
+        }
+        this
+    }
+
+    /// 注册（或覆盖）某 store path + key 的 schema。
+    pub fn register(&mut self, store_path: &str, key: &str, validator: Validator) {
+        self.validators
+            .insert((store_path.to_string(), key.to_string()), validator);
+    }
+
+    /// 校验 value；未注册 schema 时直接通过。
+    pub fn validate(
+        &self,
+        store_path: &str,
+        key: &str,
+        value: &JsonValue,
+    ) -> Result<(), Vec<SchemaViolation>> {
+        let Some(validator) = self
+            .validators
+            .get(&(store_file_name(store_path).to_string(), key.to_string()))
+        else {
+            return Ok(());
+        };
+        let violations: Vec<SchemaViolation> = validator
+            .iter_errors(value)
+            .map(|e| SchemaViolation {
+                pointer: e.instance_path.to_string(),
+                message: e.to_string(),
+            })
+            .collect();
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+/// store path 的文件名部分，即 schema 目录名；无法取得时原样返回。
+fn store_file_name(store_path: &str) -> &str {
+    Path::new(store_path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(store_path)
+}
+
+fn compile(path: &Path) -> Result<Validator, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let schema: JsonValue = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+    jsonschema::validator_for(&schema).map_err(|e| e.to_string())
+}
@@ -0,0 +1,115 @@
+//! Store 按 key 过期：过期时间集中记录在保留 key `__expires`（key → 过期时刻 Unix 秒）中，
+//! 业务 value 本身不变，core 直接读 store 文件时看到的仍是原结构。
+//!
+//! - 读取时惰性清理：已过期视为不存在，并顺手删除。
+//! - 启动时与之后每隔 [SWEEP_INTERVAL] 全量压缩一次配置中的 store 文件。
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::{Map, Number, Value as JsonValue};
+use tauri::{AppHandle, Wry};
+use tauri_plugin_store::{Store, StoreExt};
+
+use super::resolve_path;
+use crate::config;
+use crate::util::now_secs;
+
+/// 记录各 key 过期时刻的保留 key。
+pub const EXPIRY_KEY: &str = "__expires";
+
+/// 后台清理间隔。
+const SWEEP_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+fn expiry_map(store: &Store<Wry>) -> Map<String, JsonValue> {
+    match store.get(EXPIRY_KEY) {
+        Some(JsonValue::Object(m)) => m,
+        _ => Map::new(),
+    }
+}
+
+fn put_expiry_map(store: &Store<Wry>, m: Map<String, JsonValue>) {
+    if m.is_empty() {
+        store.delete(EXPIRY_KEY);
+    } else {
+        store.set(EXPIRY_KEY, JsonValue::Object(m));
+    }
+}
+
+/// 写入 key 时更新其过期时间；`ttl_secs` 为 None 时清除旧的过期记录（变为永久）。
+pub fn set_expiry(store: &Store<Wry>, key: &str, ttl_secs: Option<u64>) {
+    let mut m = expiry_map(store);
+    let changed = match ttl_secs {
+        Some(ttl) => {
+            let at = now_secs().saturating_add(ttl);
+            m.insert(key.to_string(), JsonValue::Number(Number::from(at)));
+            true
+        }
+        None => m.remove(key).is_some(),
+    };
+    if changed {
+        put_expiry_map(store, m);
+    }
+}
+
+/// 若 key 已过期则删除 value 与过期记录，返回是否发生了清理（调用方负责 save）。
+pub fn purge_if_expired(store: &Store<Wry>, key: &str) -> bool {
+    let mut m = expiry_map(store);
+    let expired = m
+        .get(key)
+        .and_then(JsonValue::as_u64)
+        .is_some_and(|at| at <= now_secs());
+    if expired {
+        m.remove(key);
+        store.delete(key);
+        put_expiry_map(store, m);
+    }
+    expired
+}
+
+/// 删除所有已过期 key，返回删除数量；有变更时落盘。
+pub fn purge_expired(store: &Arc<Store<Wry>>) -> Result<usize, String> {
+    let now = now_secs();
+    let mut m = expiry_map(store);
+    let expired: Vec<String> = m
+        .iter()
+        .filter(|(_, at)| at.as_u64().is_some_and(|at| at <= now))
+        .map(|(k, _)| k.clone())
+        .collect();
+    // 对应 value 已不存在的过期记录也一并去掉
+    let orphaned: Vec<String> = m
+        .keys()
+        .filter(|k| !store.has(k.as_str()))
+        .cloned()
+        .collect();
+    if expired.is_empty() && orphaned.is_empty() {
+        return Ok(0);
+    }
+    for k in &expired {
+        m.remove(k);
+        store.delete(k);
+    }
+    for k in &orphaned {
+        m.remove(k);
+    }
+    put_expiry_map(store, m);
+    store.save().map_err(|e| e.to_string())?;
+    Ok(expired.len())
+}
+
+/// Setup 阶段调用：立即清理一次配置中的 store 文件，之后在后台线程定期清理。
+pub fn spawn_sweeper(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        let name = config::get_store_name(&app);
+        match resolve_path(&app, &name).and_then(|p| app.store(p).map_err(|e| e.to_string())) {
+            Ok(store) => match purge_expired(&store) {
+                Ok(0) => {}
+                Ok(n) => log::info!("已清理 {} 个过期 key（{}）", n, name),
+                Err(e) => log::warn!("清理过期 key 失败: {}", e),
+            },
+            Err(e) => log::warn!("打开 {} 失败，跳过过期清理: {}", name, e),
+        }
+        std::thread::sleep(SWEEP_INTERVAL);
+    });
+}
@@ -0,0 +1,684 @@
+//! core 更新：按更新清单把更新的 core 包（以及可选的 Node 运行时）下载到 app_data，
+//! 启动时优先于随应用打包的 `resources/core` / `toolbox_node` 使用。
+//!
+//! 目录（均在 app_data 下）：
+//!
+//! ```text
+//! core-updates/
+//! ├── archives/        # 下载的压缩包
+//! ├── core/<version>/  # 解压后的 core 包（须含 index.js）
+//! ├── node/<version>/  # 解压后的 Node 运行时（bin/node 或 node.exe）
+//! └── state.json       # 当前启用的版本与失败记录，见 [UpdateState]
+//! ```
+//!
+//! - 清单地址取自 settings.json 的 `core_update_manifest_url`（支持 `file://`）；
+//! - 每个包都校验 SHA-256 与 Ed25519 签名（对压缩包原始字节签名，公钥 `core_update_public_key`
+//!   与签名均为 base64）；清单不是 `file://` 时未配置公钥一律拒绝下载与安装，
+//!   只有本地清单允许不配置公钥、仅校验 SHA-256；
+//! - 使用更新包启动的 core 未能就绪时，该版本记入 `failed` 并自动回退到内置版本重新启动，
+//!   之后的检查不再推荐已失败的版本。
+//!
+//! 清单格式（`node` 可省略；`minNodeVersion` 不满足且清单未提供合适的 Node 时拒绝安装）：
+//!
+//! ```json
+//! {
+//!   "core": {
+//!     "version": "1.1.0",
+//!     "url": "https://…/core-1.1.0.tar.gz",
+//!     "sha256": "…",
+//!     "signature": "…",
+//!     "minNodeVersion": "24.1.0"
+//!   },
+//!   "node": {
+//!     "version": "24.2.0",
+//!     "assets": {
+//!       "linux-x86_64": { "url": "https://…/node-v24.2.0-linux-x64.tar.gz", "sha256": "…", "signature": "…" }
+//!     }
+//!   }
+//! }
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use base64::Engine;
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_shell::ShellExt;
+
+use crate::clawbot::check_version_name;
+use crate::clawbot::download::{archive_extension, download_verified, fetch_text, platform_key};
+use crate::clawbot::extract::{extract_archive, MAX_UNCOMPRESSED_BYTES};
+use crate::config;
+use crate::integrity::MANIFEST_FILE_NAME;
+use crate::store::migrate::write_json_atomic;
+use crate::util::{now_secs, sha256_file};
+
+const UPDATES_DIR_NAME: &str = "core-updates";
+const STATE_FILE_NAME: &str = "state.json";
+
+/// 一个可下载的包。
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateAsset {
+    pub url: String,
+    pub sha256: String,
+    /// Ed25519 签名（base64）
+    #[serde(default)]
+    pub signature: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoreRelease {
+    pub version: String,
+    #[serde(flatten)]
+    pub asset: UpdateAsset,
+    #[serde(default)]
+    pub min_node_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NodeRelease {
+    pub version: String,
+    /// 平台（见 [platform_key]）→ 包
+    pub assets: HashMap<String, UpdateAsset>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateManifest {
+    pub core: CoreRelease,
+    #[serde(default)]
+    pub node: Option<NodeRelease>,
+}
+
+/// 对应 `<APP_DATA_DIR>/core-updates/state.json`。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UpdateState {
+    /// 启用的 core 版本；None 表示使用内置版本。
+    pub core_version: Option<String>,
+    /// 启用的 core 包中 `integrity.json` 的 SHA-256，安装时记录，供 [crate::integrity] 校验清单本身
+    pub core_manifest_sha256: Option<String>,
+    /// 启用的 Node 版本；None 表示使用内置侧车。
+    pub node_version: Option<String>,
+    /// 未能就绪而被回退的版本，形如 `core@1.1.0`、`node@24.2.0`
+    pub failed: Vec<String>,
+    /// 最近一次检查更新的时间（Unix 秒）
+    pub last_check: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+/// 当前 core 进程实际使用的更新版本（由 [preferred_core_dir] / [preferred_node] 记录），
+/// 供就绪失败时判断是否需要回退。
+#[derive(Debug, Clone, Default)]
+struct InUse {
+    core: Option<String>,
+    node: Option<String>,
+}
+
+static IN_USE: Mutex<InUse> = Mutex::new(InUse {
+    core: None,
+    node: None,
+});
+
+/// `core_update_check` 的返回值。
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateCheck {
+    pub bundled_core_version: Option<String>,
+    pub bundled_node_version: Option<String>,
+    pub active_core_version: Option<String>,
+    pub active_node_version: Option<String>,
+    /// 当前 core 进程是否在使用更新包
+    pub in_use: bool,
+    pub latest_core_version: String,
+    pub latest_node_version: Option<String>,
+    pub core_available: bool,
+    pub node_available: bool,
+    pub failed: Vec<String>,
+}
+
+/// `core-update-progress` 事件负载。
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateProgress<'a> {
+    component: &'a str,
+    version: &'a str,
+    bytes: u64,
+    total: Option<u64>,
+    rate: u64,
+}
+
+// ---------------------------------------------------------------------------
+// 目录与状态
+// ---------------------------------------------------------------------------
+
+/// `<app_data>/core-updates`
+pub fn updates_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let app_data = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(app_data.join(UPDATES_DIR_NAME))
+}
+
+pub fn read_state(dir: &Path) -> UpdateState {
+    fs::read_to_string(dir.join(STATE_FILE_NAME))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn update_state(dir: &Path, f: impl FnOnce(&mut UpdateState)) -> Result<UpdateState, String> {
+    let mut state = read_state(dir);
+    f(&mut state);
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let value = serde_json::to_value(&state).map_err(|e| e.to_string())?;
+    write_json_atomic(&dir.join(STATE_FILE_NAME), &value)?;
+    Ok(state)
+}
+
+fn failed_key(component: &str, version: &str) -> String {
+    format!("{}@{}", component, version)
+}
+
+/// Node 运行时目录中的可执行文件：根目录或唯一子目录（官方包 `node-v…/`）下的
+/// `bin/node`（Windows 为 `node.exe`）。
+fn find_node_binary(dir: &Path) -> Option<PathBuf> {
+    let rel = if cfg!(windows) {
+        PathBuf::from("node.exe")
+    } else {
+        Path::new("bin").join("node")
+    };
+    if dir.join(&rel).is_file() {
+        return Some(dir.join(rel));
+    }
+    let mut subdirs = fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir());
+    let only = subdirs.next()?;
+    if subdirs.next().is_some() {
+        return None;
+    }
+    let bin = only.join(rel);
+    bin.is_file().then_some(bin)
+}
+
+/// 启用且未失败的更新 core 目录（须含 index.js）；同时记录为「使用中」。
+pub fn preferred_core_dir(app: &AppHandle) -> Option<PathBuf> {
+    let selected = updates_dir(app).ok().and_then(|dir| {
+        let state = read_state(&dir);
+        let version = state.core_version?;
+        if state.failed.contains(&failed_key("core", &version)) {
+            return None;
+        }
+        let core = dir.join("core").join(&version);
+        core.join("index.js").is_file().then_some((version, core))
+    });
+    if let Ok(mut in_use) = IN_USE.lock() {
+        in_use.core = selected.as_ref().map(|(v, _)| v.clone());
+    }
+    selected.map(|(_, core)| core)
+}
+
+/// `core_dir` 为启用中的更新 core 目录时，返回安装时记录的 `integrity.json` 摘要（未记录则为空串）；
+/// 否则返回 None，由调用方使用内置 core 的摘要。
+pub fn trusted_manifest_digest(app: &AppHandle, core_dir: &Path) -> Option<String> {
+    let dir = updates_dir(app).ok()?;
+    let state = read_state(&dir);
+    let version = state.core_version?;
+    (dir.join("core").join(version) == core_dir)
+        .then(|| state.core_manifest_sha256.unwrap_or_default())
+}
+
+/// 启用且未失败的更新 Node 可执行文件；同时记录为「使用中」。
+pub fn preferred_node(app: &AppHandle) -> Option<PathBuf> {
+    let selected = updates_dir(app).ok().and_then(|dir| {
+        let state = read_state(&dir);
+        let version = state.node_version?;
+        if state.failed.contains(&failed_key("node", &version)) {
+            return None;
+        }
+        find_node_binary(&dir.join("node").join(&version)).map(|bin| (version, bin))
+    });
+    if let Ok(mut in_use) = IN_USE.lock() {
+        in_use.node = selected.as_ref().map(|(v, _)| v.clone());
+    }
+    selected.map(|(_, bin)| bin)
+}
+
+/// core 未能就绪时调用：若正在使用更新包，把使用中的版本记为失败并停用，返回 true
+/// （调用方随后用内置版本重启）；未使用更新包时返回 false。
+pub fn fall_back(app: &AppHandle, reason: &str) -> bool {
+    let in_use = match IN_USE.lock() {
+        Ok(mut guard) => std::mem::take(&mut *guard),
+        Err(_) => return false,
+    };
+    if in_use.core.is_none() && in_use.node.is_none() {
+        return false;
+    }
+    let Ok(dir) = updates_dir(app) else {
+        return false;
+    };
+    let result = update_state(&dir, |s| {
+        for (component, version) in [("core", &in_use.core), ("node", &in_use.node)] {
+            let Some(version) = version else {
+                continue;
+            };
+            let key = failed_key(component, version);
+            if !s.failed.contains(&key) {
+                s.failed.push(key);
+            }
+        }
+        if in_use.core.is_some() {
+            s.core_version = None;
+            s.core_manifest_sha256 = None;
+        }
+        if in_use.node.is_some() {
+            s.node_version = None;
+        }
+        s.last_error = Some(reason.to_string());
+    });
+    if let Err(e) = result {
+        log::warn!("记录失败版本出错: {}", e);
+        return false;
+    }
+    log::warn!(
+        "更新包未能就绪（core {:?}, node {:?}），回退到内置版本: {}",
+        in_use.core,
+        in_use.node,
+        reason
+    );
+    let _ = app.emit(
+        "core-update-fallback",
+        serde_json::json!({
+            "coreVersion": in_use.core,
+            "nodeVersion": in_use.node,
+            "reason": reason,
+        }),
+    );
+    true
+}
+
+// ---------------------------------------------------------------------------
+// 版本与校验
+// ---------------------------------------------------------------------------
+
+/// 比较 `1.2.10` 与 `v1.2.9` 这类版本：忽略前缀 `v` 与 `-` 之后的预发布部分，逐段按数字比较。
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn parts(v: &str) -> Vec<u64> {
+        let v = v.trim().trim_start_matches('v');
+        let v = v.split(['-', '+']).next().unwrap_or_default();
+        v.split('.').map(|p| p.parse().unwrap_or(0)).collect()
+    }
+    let (a, b) = (parts(a), parts(b));
+    let len = a.len().max(b.len());
+    (0..len)
+        .map(|i| {
+            let x = a.get(i).copied().unwrap_or(0);
+            let y = b.get(i).copied().unwrap_or(0);
+            x.cmp(&y)
+        })
+        .find(|o| o.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// 更新包签名公钥：清单不是 `file://` 时必须配置，否则拒绝；本地清单未配置时返回 None。
+fn signing_key(app: &AppHandle) -> Result<Option<String>, String> {
+    let key = config::get_core_update_public_key(app);
+    let local = config::get_core_update_manifest_url(app)
+        .is_some_and(|u| u.to_ascii_lowercase().starts_with("file://"));
+    if key.is_none() && !local {
+        return Err("未配置 core_update_public_key，拒绝从远程清单下载或安装更新".to_string());
+    }
+    Ok(key)
+}
+
+/// 校验 Ed25519 签名；`public_key` 为 None（仅本地清单，见 [signing_key]）时跳过，否则签名必填。
+fn verify_signature(
+    public_key: Option<&str>,
+    file: &Path,
+    signature: Option<&str>,
+) -> Result<(), String> {
+    let Some(public_key) = public_key else {
+        return Ok(());
+    };
+    let signature = signature.ok_or_else(|| "已配置公钥，但更新包缺少签名".to_string())?;
+    let b64 = base64::engine::general_purpose::STANDARD;
+    let key = b64
+        .decode(public_key.trim())
+        .map_err(|e| format!("公钥不是有效的 base64: {}", e))?;
+    let sig = b64
+        .decode(signature.trim())
+        .map_err(|e| format!("签名不是有效的 base64: {}", e))?;
+    let data = fs::read(file).map_err(|e| e.to_string())?;
+    UnparsedPublicKey::new(&ED25519, key)
+        .verify(&data, &sig)
+        .map_err(|_| format!("签名校验失败: {}", file.display()))
+}
+
+/// 内置 core 的版本：`resources/core/package.json` 的 version。
+fn bundled_core_version(app: &AppHandle) -> Option<String> {
+    let path = app
+        .path()
+        .resource_dir()
+        .ok()?
+        .join("core")
+        .join("package.json");
+    let text = fs::read_to_string(path).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&text).ok()?;
+    json.get("version")?.as_str().map(String::from)
+}
+
+/// 执行 `<node> --version`（`node` 为 None 时用内置侧车），返回去掉 `v` 前缀的版本。
+async fn node_version(app: &AppHandle, node: Option<&Path>) -> Option<String> {
+    let cmd = match node {
+        Some(bin) => app.shell().command(bin.to_string_lossy().to_string()),
+        None => app.shell().sidecar("toolbox_node").ok()?,
+    };
+    let output = cmd.args(["--version"]).output().await.ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let version = text.trim().trim_start_matches('v');
+    (!version.is_empty()).then(|| version.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// 下载与安装
+// ---------------------------------------------------------------------------
+
+pub async fn fetch_manifest(app: &AppHandle) -> Result<UpdateManifest, String> {
+    let url = config::get_core_update_manifest_url(app)
+        .ok_or_else(|| "未配置 core_update_manifest_url".to_string())?;
+    signing_key(app)?;
+    let text = fetch_text(&url).await?;
+    serde_json::from_str(&text).map_err(|e| format!("更新清单格式错误: {}", e))
+}
+
+/// 下载、校验并解压一个包到 `core-updates/<component>/<version>/`，返回安装目录。
+/// 已有同版本且校验通过的压缩包时直接复用。
+async fn install_asset(
+    app: &AppHandle,
+    dir: &Path,
+    component: &'static str,
+    version: &str,
+    asset: &UpdateAsset,
+) -> Result<PathBuf, String> {
+    check_version_name(version)?;
+    let public_key = signing_key(app)?;
+    let ext = archive_extension(&asset.url)?;
+    let archive = dir
+        .join("archives")
+        .join(archive_file_name(component, version, ext));
+    let reusable = archive.exists()
+        && sha256_file(&archive).is_ok_and(|h| h.eq_ignore_ascii_case(asset.sha256.trim()));
+    if !reusable {
+        let emitter = app.clone();
+        download_verified(&asset.url, &asset.sha256, &archive, |bytes, total, rate| {
+            let _ = emitter.emit(
+                "core-update-progress",
+                UpdateProgress {
+                    component,
+                    version,
+                    bytes,
+                    total,
+                    rate,
+                },
+            );
+        })
+        .await?;
+    }
+    if let Err(e) = verify_signature(public_key.as_deref(), &archive, asset.signature.as_deref()) {
+        let _ = fs::remove_file(&archive);
+        return Err(e);
+    }
+
+    let dest = dir.join(component).join(version);
+    let src = archive.clone();
+    let target = dest.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        extract_archive(&src, &target, MAX_UNCOMPRESSED_BYTES)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    Ok(dest)
+}
+
+/// 删除 `core-updates/<component>/` 与压缩包中除 `keep` 以外的版本。
+/// `archives/` 下的压缩包文件名：`<component>-<version>.<ext>`。
+fn archive_file_name(component: &str, version: &str, ext: &str) -> String {
+    format!("{}-{}.{}", component, version, ext)
+}
+
+fn prune(dir: &Path, component: &str, keep: Option<&str>) {
+    let keep_archives: Vec<String> = keep
+        .map(|v| {
+            ["zip", "tar.gz"]
+                .iter()
+                .map(|ext| archive_file_name(component, v, ext))
+                .collect()
+        })
+        .unwrap_or_default();
+    if let Ok(entries) = fs::read_dir(dir.join(component)) {
+        for path in entries.flatten().map(|e| e.path()) {
+            let name = path.file_name().map(|n| n.to_string_lossy().to_string());
+            if path.is_dir() && name.as_deref() != keep {
+                let _ = fs::remove_dir_all(&path);
+            }
+        }
+    }
+    if let Ok(entries) = fs::read_dir(dir.join("archives")) {
+        for path in entries.flatten().map(|e| e.path()) {
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let ours = name.starts_with(&format!("{}-", component));
+            let kept = keep_archives.contains(&name);
+            if ours && !kept {
+                let _ = fs::remove_file(&path);
+            }
+        }
+    }
+}
+
+/// 在后台线程重启 core，使新的目录选择生效。
+fn restart_core_async(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = crate::core::restart_core(&app) {
+            log::error!("重启 core 失败: {}", e);
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// 命令
+// ---------------------------------------------------------------------------
+
+/// 读取更新清单并与内置 / 已启用版本比较；已失败过的版本不算可用更新。
+#[tauri::command]
+pub async fn core_update_check(app: AppHandle) -> Result<UpdateCheck, String> {
+    let dir = updates_dir(&app)?;
+    let manifest = match fetch_manifest(&app).await {
+        Ok(m) => m,
+        Err(e) => {
+            let msg = e.clone();
+            let _ = update_state(&dir, |s| {
+                s.last_check = Some(now_secs());
+                s.last_error = Some(msg);
+            });
+            return Err(e);
+        }
+    };
+    let state = update_state(&dir, |s| {
+        s.last_check = Some(now_secs());
+        s.last_error = None;
+    })?;
+
+    let bundled_core = bundled_core_version(&app);
+    let bundled_node = node_version(&app, None).await;
+    let current_core = state.core_version.clone().or(bundled_core.clone());
+    let current_node = state.node_version.clone().or(bundled_node.clone());
+    let newer = |latest: &str, current: &Option<String>| {
+        current
+            .as_deref()
+            .is_none_or(|c| compare_versions(latest, c) == Ordering::Greater)
+    };
+
+    let latest_core = manifest.core.version.clone();
+    let core_available = newer(&latest_core, &current_core)
+        && !state.failed.contains(&failed_key("core", &latest_core));
+    let node = manifest
+        .node
+        .as_ref()
+        .filter(|n| n.assets.contains_key(&platform_key()));
+    let latest_node = node.map(|n| n.version.clone());
+    let node_available = latest_node
+        .as_deref()
+        .is_some_and(|v| newer(v, &current_node) && !state.failed.contains(&failed_key("node", v)));
+    let in_use = IN_USE
+        .lock()
+        .map(|u| u.core.is_some() || u.node.is_some())
+        .unwrap_or(false);
+
+    Ok(UpdateCheck {
+        bundled_core_version: bundled_core,
+        bundled_node_version: bundled_node,
+        active_core_version: state.core_version,
+        active_node_version: state.node_version,
+        in_use,
+        latest_core_version: latest_core,
+        latest_node_version: latest_node,
+        core_available,
+        node_available,
+        failed: state.failed,
+    })
+}
+
+/// 下载并启用清单中的 core（及需要时的 Node 运行时），清理其他已下载版本。
+/// `restart` 为 true（默认）时立即重启 core；新包未能就绪会自动回退到内置版本。
+#[tauri::command]
+pub async fn core_update_install(
+    app: AppHandle,
+    restart: Option<bool>,
+) -> Result<UpdateState, String> {
+    let dir = updates_dir(&app)?;
+    let manifest = fetch_manifest(&app).await?;
+    let state = read_state(&dir);
+    let core = &manifest.core;
+    if state.failed.contains(&failed_key("core", &core.version)) {
+        return Err(format!("core {} 曾启动失败，不再安装", core.version));
+    }
+    // 只接受比当前（已启用或内置）更新的版本：签名只覆盖压缩包，重放旧的已签名清单不能降级
+    let current_core = state
+        .core_version
+        .clone()
+        .or_else(|| bundled_core_version(&app));
+    if let Some(current) = current_core {
+        if compare_versions(&core.version, &current) != Ordering::Greater {
+            return Err(format!(
+                "清单中的 core {} 不比当前版本 {} 新，拒绝安装",
+                core.version, current
+            ));
+        }
+    }
+
+    // 确定运行新 core 所用的 Node：清单提供更新的 Node 时一并安装
+    let node_asset = manifest.node.as_ref().and_then(|n| {
+        let asset = n.assets.get(&platform_key())?;
+        let failed = state.failed.contains(&failed_key("node", &n.version));
+        (!failed).then(|| (n.version.clone(), asset.clone()))
+    });
+    let current_node = match state.node_version.clone() {
+        Some(v) => Some(v),
+        None => node_version(&app, None).await,
+    };
+    let install_node = node_asset.filter(|(v, _)| {
+        current_node
+            .as_deref()
+            .is_none_or(|c| compare_versions(v, c) == Ordering::Greater)
+    });
+    let effective_node = install_node
+        .as_ref()
+        .map(|(v, _)| v.clone())
+        .or(current_node);
+    if let (Some(min), Some(node)) = (&core.min_node_version, &effective_node) {
+        if compare_versions(node, min) == Ordering::Less {
+            return Err(format!(
+                "core {} 需要 Node {}，当前为 {}",
+                core.version, min, node
+            ));
+        }
+    }
+
+    let result = async {
+        if let Some((version, asset)) = &install_node {
+            let installed = install_asset(&app, &dir, "node", version, asset).await?;
+            if find_node_binary(&installed).is_none() {
+                let _ = fs::remove_dir_all(&installed);
+                return Err(format!("Node {} 包中没有可执行文件", version));
+            }
+        }
+        let installed = install_asset(&app, &dir, "core", &core.version, &core.asset).await?;
+        if !installed.join("index.js").is_file() {
+            let _ = fs::remove_dir_all(&installed);
+            return Err(format!("core {} 包中没有 index.js", core.version));
+        }
+        // 包已通过签名与 SHA-256 校验，此时的清单摘要作为之后启动时的可信值
+        sha256_file(&installed.join(MANIFEST_FILE_NAME)).map_err(|e| {
+            let _ = fs::remove_dir_all(&installed);
+            format!(
+                "core {} 包中没有可读的 {}: {}",
+                core.version, MANIFEST_FILE_NAME, e
+            )
+        })
+    }
+    .await;
+    let manifest_sha256 = match result {
+        Ok(digest) => digest,
+        Err(e) => {
+            let msg = e.clone();
+            let _ = update_state(&dir, |s| s.last_error = Some(msg));
+            return Err(e);
+        }
+    };
+
+    let state = update_state(&dir, |s| {
+        s.core_version = Some(core.version.clone());
+        s.core_manifest_sha256 = Some(manifest_sha256);
+        if let Some((version, _)) = &install_node {
+            s.node_version = Some(version.clone());
+        }
+        s.last_error = None;
+    })?;
+    prune(&dir, "core", state.core_version.as_deref());
+    prune(&dir, "node", state.node_version.as_deref());
+    log::info!(
+        "已启用 core {:?}，node {:?}",
+        state.core_version,
+        state.node_version
+    );
+    if restart.unwrap_or(true) {
+        restart_core_async(&app);
+    }
+    Ok(state)
+}
+
+/// 停用更新包，回到内置的 core 与 Node；已下载的文件保留到下次安装时清理。
+#[tauri::command]
+pub fn core_update_rollback(app: AppHandle, restart: Option<bool>) -> Result<UpdateState, String> {
+    let dir = updates_dir(&app)?;
+    let state = update_state(&dir, |s| {
+        s.core_version = None;
+        s.core_manifest_sha256 = None;
+        s.node_version = None;
+    })?;
+    if restart.unwrap_or(true) {
+        restart_core_async(&app);
+    }
+    Ok(state)
+}
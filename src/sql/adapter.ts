@@ -1,34 +1,24 @@
 import type Database from "@tauri-apps/plugin-sql";
-import { store } from "@/store";
-import { useTauriConfigStore } from "@/store/modules/tauriConfig";
+import { invoke } from "@tauri-apps/api/core";
 
-/** 从 Pinia 读取（main 启动时已通过 IPC get_config 从 resource/settings.json 注入），缺省 test.db */
-function getDbPath(): string {
-  const name = useTauriConfigStore(store).sqliteDbName;
-  return `sqlite:${name}`;
+/**
+ * 当前 profile 的数据库绝对路径（IPC db_location），与 core、Rust 迁移为同一文件。
+ * 表结构只由 src-tauri/migrations 维护，前端不执行 DDL。
+ */
+async function getDbUrl(): Promise<string> {
+  const path = await invoke<string>("db_location");
+  return `sqlite:${path}`;
 }
 
-const TEST_TABLE_SQL = `
-CREATE TABLE IF NOT EXISTS test (
-  id INTEGER PRIMARY KEY AUTOINCREMENT,
-  name TEXT
-)
-`.trim();
-
 let dbInstance: Database | null = null;
-let tableEnsured = false;
 
-/** 获取 Tauri 数据库实例（单例），首次调用时自动创建 test 表 */
+/** 获取 Tauri 数据库实例（单例；切换 profile 后页面重新加载，随之重新定位） */
 export async function getTauriDb(): Promise<Database> {
   if (dbInstance) {
     return dbInstance;
   }
   const Database = (await import("@tauri-apps/plugin-sql")).default;
-  dbInstance = await Database.load(getDbPath());
-  if (!tableEnsured) {
-    await dbInstance.execute(TEST_TABLE_SQL);
-    tableEnsured = true;
-  }
+  dbInstance = await Database.load(await getDbUrl());
   return dbInstance;
 }
 
@@ -1,4 +1,3 @@
-import { getTauriDb } from "./adapter";
 import { db, testTable } from "./db";
 
 export const testSql = async () => {
@@ -6,19 +5,6 @@ export const testSql = async () => {
   return rows;
 };
 
-export const createTable = async () => {
-  try {
-    const tauriDb = await getTauriDb();
-    await tauriDb.execute(
-      "CREATE TABLE IF NOT EXISTS test (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT)"
-    );
-    return "Table created";
-  } catch (error) {
-    console.error(error);
-    return "Table created failed";
-  }
-};
-
 export const insertData = async (name = "test") => {
   try {
     await db.insert(testTable).values({ name }).run();
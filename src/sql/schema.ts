@@ -1,6 +1,6 @@
 import { integer, sqliteTable, text } from "drizzle-orm/sqlite-core";
 
-/** 与 createTable 中建表语句一致 */
+/** 与 src-tauri/migrations/0001_create_test.sql 一致（表结构只由迁移维护） */
 export const testTable = sqliteTable("test", {
   id: integer("id").primaryKey({ autoIncrement: true }),
   name: text("name"),
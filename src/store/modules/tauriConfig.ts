@@ -6,16 +6,52 @@ export interface TauriAppConfig {
   sqlite_db_name: string;
   api_port: number;
   store_name: string;
+  /** 为 true 时 store_read/store_write 透明加解密 */
+  store_encrypt: boolean;
+  /** Clawbot 发布清单地址，空串表示未配置 */
+  clawbot_manifest_url: string;
+  /** core 更新清单地址，空串表示不检查更新 */
+  core_update_manifest_url: string;
+  /** core 更新包签名公钥（Ed25519，base64）；空串时只允许 file:// 清单（仅校验 SHA-256），远程清单一律拒绝 */
+  core_update_public_key: string;
+  /** core 完整性校验策略："enforce" | "warn" | "off"，空串表示按构建类型（release 拒绝、debug 警告） */
+  core_integrity_policy: string;
+  /** 数据库定时备份间隔（小时），0 表示默认 24 */
+  db_backup_interval_hours: number;
+  /** 数据库备份最多保留份数，0 表示默认 10 */
+  db_backup_keep: number;
+  /** 数据库备份最长保留天数，0 表示默认 30 */
+  db_backup_max_age_days: number;
+  /** 日志级别："error" | "warn" | "info" | "debug" | "trace"，空串表示按构建类型（release info、debug debug）；环境变量 LOG_LEVEL 优先 */
+  log_level: string;
 }
 
 const DEFAULT_SQLITE_DB_NAME = "test.db";
 const DEFAULT_API_PORT = 8264;
 const DEFAULT_STORE_NAME = "";
+const DEFAULT_STORE_ENCRYPT = false;
+const DEFAULT_CLAWBOT_MANIFEST_URL = "";
+const DEFAULT_CORE_UPDATE_MANIFEST_URL = "";
+const DEFAULT_CORE_UPDATE_PUBLIC_KEY = "";
+const DEFAULT_CORE_INTEGRITY_POLICY = "";
+const DEFAULT_DB_BACKUP_INTERVAL_HOURS = 24;
+const DEFAULT_DB_BACKUP_KEEP = 10;
+const DEFAULT_DB_BACKUP_MAX_AGE_DAYS = 30;
+const DEFAULT_LOG_LEVEL = "";
 const useTauriConfigStore = defineStore("tauriConfig", {
   state: (): TauriAppConfig => ({
     sqlite_db_name: DEFAULT_SQLITE_DB_NAME,
     api_port: DEFAULT_API_PORT,
     store_name: DEFAULT_STORE_NAME,
+    store_encrypt: DEFAULT_STORE_ENCRYPT,
+    clawbot_manifest_url: DEFAULT_CLAWBOT_MANIFEST_URL,
+    core_update_manifest_url: DEFAULT_CORE_UPDATE_MANIFEST_URL,
+    core_update_public_key: DEFAULT_CORE_UPDATE_PUBLIC_KEY,
+    core_integrity_policy: DEFAULT_CORE_INTEGRITY_POLICY,
+    db_backup_interval_hours: DEFAULT_DB_BACKUP_INTERVAL_HOURS,
+    db_backup_keep: DEFAULT_DB_BACKUP_KEEP,
+    db_backup_max_age_days: DEFAULT_DB_BACKUP_MAX_AGE_DAYS,
+    log_level: DEFAULT_LOG_LEVEL,
   }),
   getters: {
     /** 供 SQL adapter 使用：sqlite:${name} */
@@ -1,18 +1,34 @@
-import { LazyStore } from '@tauri-apps/plugin-store';
+import { invoke } from '@tauri-apps/api/core';
 import { useTauriConfigStore } from '@/store/modules/tauriConfig';
 
-/** Tauri 持久化键值存储，首次使用时懒加载。API：set/get 等均为 async */
+/**
+ * Tauri 持久化键值存储。读写统一走 Rust 命令 store_read / store_write / store_entries，
+ * 以便加密（store_encrypt）、schema 校验、过期与写入前快照生效；不要直接使用 store 插件。
+ * store 文件位于当前 profile 的数据目录下，path 只传 store 名（切换 profile 后页面会重新加载）。
+ */
+export interface AppStore {
+  get<T = unknown>(key: string): Promise<T | null>;
+  /** `ttlSecs` 为过期秒数，不传时清除已有过期设置 */
+  set(key: string, value: unknown, ttlSecs?: number): Promise<void>;
+  /** 全部 key 与值（已解密，不含保留 key），按 key 排序 */
+  entries<T = unknown>(): Promise<[string, T][]>;
+}
 
-export const store = async (): Promise<InstanceType<typeof LazyStore>> => {
-  const storeName = useTauriConfigStore().store_name;
-  if (!storeName) throw new Error('Store name is not set');
-  return new LazyStore(storeName);
+export const store = async (): Promise<AppStore> => {
+  const path = useTauriConfigStore().store_name;
+  if (!path) throw new Error('Store name is not set');
+  return {
+    get: async <T>(key: string) => (await invoke<T | null>('store_read', { path, key })) ?? null,
+    set: (key, value, ttlSecs) =>
+      invoke<void>('store_write', { path, key, value, ttlSecs: ttlSecs ?? null }),
+    entries: <T>() => invoke<[string, T][]>('store_entries', { path }),
+  };
 };
 
-let storeInstance: InstanceType<typeof LazyStore> | null = null;
+let storeInstance: AppStore | null = null;
 
-/** 供 ai.ts 等子模块使用，首次使用时懒加载 */
-export async function getStore(): Promise<InstanceType<typeof LazyStore> | null> {
+/** 供 ai.ts 等子模块使用，首次使用时创建 */
+export async function getStore(): Promise<AppStore | null> {
   if (storeInstance) return storeInstance;
   try {
     storeInstance = await store();
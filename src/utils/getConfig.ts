@@ -1,12 +1,57 @@
 import type { Pinia } from "pinia";
 import { useTauriConfigStore } from "@/store/modules/tauriConfig";
 import { setCoreReady } from "@/utils/axios/tauriHttp";
-import { ElMessage } from "element-plus";
+import { ElMessage, ElMessageBox } from "element-plus";
+
+/** second-instance 事件：`open <file>` / `run-script <name>` / 仅聚焦 */
+export type SecondInstancePayload = (
+  | { action: "focus" }
+  | { action: "open"; path: string }
+  | { action: "run-script"; name: string }
+) & { args: string[]; cwd: string };
+
+/** deep-link 事件：后端已按白名单校验过的 toolbox:// 链接 */
+export type DeepLinkPayload = (
+  | { action: "profile/switch"; name: string }
+  | { action: "clawbot/install"; version: string | null }
+) & { url: string };
+
+/** 链接可能来自任意网页，执行前须经用户确认 */
+async function runDeepLink(payload: DeepLinkPayload): Promise<void> {
+  const { invoke } = await import("@tauri-apps/api/core");
+  const message =
+    payload.action === "profile/switch"
+      ? `切换到 profile「${payload.name}」？`
+      : `安装 Clawbot ${payload.version ?? "最新版本"}？`;
+  try {
+    await ElMessageBox.confirm(message, "打开链接", { type: "warning" });
+  } catch {
+    return;
+  }
+  try {
+    if (payload.action === "profile/switch") {
+      await invoke("profile_switch", { name: payload.name });
+    } else {
+      await invoke("clawbot_download", { version: payload.version });
+      ElMessage.success("Clawbot 已安装");
+    }
+  } catch (e) {
+    ElMessage.error(String(e));
+  }
+}
+
+function onDeepLinkFailed(payload: { url: string; reason: string }): void {
+  console.warn("[getConfig] 收到 deep-link-failed 事件:", payload);
+  ElMessage.warning(`无法打开链接：${payload.reason}`);
+}
 
 /**
  * 从 IPC get_config 读取 settings.json（后端直接返回 JSON），解析后写入 Pinia。
  * 需在 Pinia 安装后调用；非 Tauri 或失败时保留 store 默认值。
- * 同时监听 core-ready 事件，更新服务就绪状态。
+ * 同时监听 core-ready 事件，更新服务就绪状态；监听 core-integrity-failed 事件并提示；
+ * 监听 profile-switched 事件并重新加载页面，使 store 与状态切换到新 profile；
+ * 把再次启动应用时转发的命令（second-instance）转为 window 上的同名 CustomEvent，供各页面订阅；
+ * 处理 toolbox:// 深链接（deep-link），经用户确认后调用对应命令。
  */
 export async function initTauriConfig(pinia: Pinia): Promise<void> {
   try {
@@ -25,6 +70,45 @@ export async function initTauriConfig(pinia: Pinia): Promise<void> {
       }
     });
     console.log("[getConfig] 已监听 core-ready 事件");
+
+    // Core 包完整性校验失败：enforce 策略下不会启动，warn 策略下仅提示
+    await listen("core-integrity-failed", (event) => {
+      const payload = event.payload as { coreDir: string; refused: boolean; report: Record<string, unknown> };
+      console.error("[getConfig] 收到 core-integrity-failed 事件:", payload);
+      if (payload.refused) {
+        ElMessage.error("Core 文件校验失败，已拒绝启动，请重新安装应用");
+      } else {
+        ElMessage.warning("Core 文件校验未通过，已按配置继续启动");
+      }
+    });
+
+    // 切换 profile 后 store 路径与 core 数据均已变化，整页重新加载
+    await listen("profile-switched", (event) => {
+      console.log("[getConfig] 收到 profile-switched 事件:", event.payload);
+      window.location.reload();
+    });
+
+    // 第二次启动转发的命令；首次启动就带的命令在前端就绪后取一次
+    const onInstanceAction = (payload: SecondInstancePayload) => {
+      console.log("[getConfig] 收到启动命令:", payload);
+      window.dispatchEvent(new CustomEvent("second-instance", { detail: payload }));
+    };
+    await listen<SecondInstancePayload>("second-instance", (event) => onInstanceAction(event.payload));
+    await listen("second-instance-failed", (event) => {
+      console.warn("[getConfig] 收到 second-instance-failed 事件:", event.payload);
+      ElMessage.warning("启动参数无效，已忽略");
+    });
+    const launch = await invoke<SecondInstancePayload | null>("instance_launch_action");
+    if (launch) onInstanceAction(launch);
+
+    // toolbox:// 深链接；前端就绪前到达的（如由链接启动应用）一次取回
+    await listen<DeepLinkPayload>("deep-link", (event) => void runDeepLink(event.payload));
+    await listen<{ url: string; reason: string }>("deep-link-failed", (event) => onDeepLinkFailed(event.payload));
+    const pending = await invoke<{ event: string; payload: unknown }[]>("deep_link_take_pending");
+    for (const { event, payload } of pending) {
+      if (event === "deep-link") void runDeepLink(payload as DeepLinkPayload);
+      else onDeepLinkFailed(payload as { url: string; reason: string });
+    }
   } catch {
     // 非 Tauri 或未就绪，使用 store 默认值
   }